@@ -22,10 +22,10 @@ pub async fn resolve(bot: &Bot) -> Result<String, DiscordErr> {
                     let s = url.clone();
                     Ok(s)
                 },
-                _ => Err(DiscordErr)
+                _ => Err(DiscordErr::InvalidResponse)
             }
         },
-        Err(_) => Err(DiscordErr)
+        Err(e) => Err(DiscordErr::Http(e))
     }
 }
 
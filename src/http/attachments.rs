@@ -0,0 +1,219 @@
+use std::io;
+use std::path::Path;
+
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Body, Method};
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::messages::CreateMessage;
+use super::Http;
+use crate::types::messages::{Attachment, Message};
+use crate::types::snowflakes::Snowflake;
+use crate::DiscordErr;
+
+const SPOILER_PREFIX: &str = "SPOILER_"; // clients blur attachments whose name starts with this
+const CHUNK_SIZE: usize = 64 * 1024; // bytes read from a file or reader per streamed chunk
+
+type Reader = Box<dyn AsyncRead + Send + Sync + Unpin>;
+
+enum Source {
+    Bytes(Vec<u8>),
+    Reader(Reader, u64),
+}
+
+// a file to upload with a message; readers and paths are streamed rather than
+// loaded into memory
+pub struct AttachmentFile {
+    filename: String,
+    description: Option<String>,
+    spoiler: bool,
+    source: Source,
+}
+
+impl AttachmentFile {
+    pub fn from_bytes(filename: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        AttachmentFile::new(filename.into(), Source::Bytes(data.into()))
+    }
+
+    // len must be the exact number of bytes the reader will produce
+    pub fn from_reader(
+        filename: impl Into<String>,
+        reader: impl AsyncRead + Send + Sync + Unpin + 'static,
+        len: u64,
+    ) -> Self {
+        AttachmentFile::new(filename.into(), Source::Reader(Box::new(reader), len))
+    }
+
+    // opens the file now and uploads it under its own name
+    pub async fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+        let file = tokio::fs::File::open(path).await?;
+        let len = file.metadata().await?.len();
+        Ok(AttachmentFile::from_reader(filename, file, len))
+    }
+
+    fn new(filename: String, source: Source) -> Self {
+        AttachmentFile {
+            filename,
+            description: None,
+            spoiler: false,
+            source,
+        }
+    }
+
+    // alt text for the file (max 1024 characters)
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn spoiler(mut self, spoiler: bool) -> Self {
+        self.spoiler = spoiler;
+        self
+    }
+
+    // the name the file is uploaded under, including the spoiler prefix
+    pub fn filename(&self) -> String {
+        if self.spoiler && !self.filename.starts_with(SPOILER_PREFIX) {
+            format!("{}{}", SPOILER_PREFIX, self.filename)
+        } else {
+            self.filename.clone()
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        match &self.source {
+            Source::Bytes(data) => data.len() as u64,
+            Source::Reader(_, len) => *len,
+        }
+    }
+
+    fn into_part(self) -> Part {
+        let filename = self.filename();
+        let part = match self.source {
+            Source::Bytes(data) => Part::bytes(data),
+            Source::Reader(reader, len) => {
+                Part::stream_with_length(Body::wrap_stream(read_chunks(reader)), len)
+            }
+        };
+        part.file_name(filename)
+    }
+}
+
+fn read_chunks(reader: Reader) -> impl Stream<Item = io::Result<Vec<u8>>> {
+    stream::try_unfold(reader, |mut reader| async move {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let n = reader.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(n);
+        Ok(Some((chunk, reader)))
+    })
+}
+
+impl Http {
+    // sends a message with files attached. upload_limit is usually
+    // Guild::upload_limit, or DEFAULT_UPLOAD_LIMIT in dms; the request isn't sent
+    // if the files add up to more. a multipart body can't be resent, so a 429 is
+    // returned as DiscordErr::RateLimited instead of being retried
+    pub async fn create_message_with_files(
+        &self,
+        channel_id: Snowflake,
+        message: &CreateMessage,
+        files: Vec<AttachmentFile>,
+        upload_limit: u64,
+    ) -> Result<Message, DiscordErr> {
+        message.validate()?;
+        let size = files.iter().map(AttachmentFile::size).sum();
+        if size > upload_limit {
+            return Err(DiscordErr::TooLarge {
+                size,
+                limit: upload_limit,
+            });
+        }
+
+        let attachments: Vec<Value> = files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| {
+                let mut attachment = json!({ "id": i, "filename": file.filename() });
+                if let Some(description) = &file.description {
+                    attachment["description"] = json!(description);
+                }
+                attachment
+            })
+            .collect();
+        let mut payload = serde_json::to_value(message)?;
+        payload["attachments"] = Value::Array(attachments);
+
+        let mut form = Form::new().text("payload_json", payload.to_string());
+        for (i, file) in files.into_iter().enumerate() {
+            form = form.part(format!("files[{}]", i), file.into_part());
+        }
+        let path = format!("/channels/{}/messages", channel_id);
+        self.json(self.request(Method::POST, &path).multipart(form))
+            .await
+    }
+
+    // streams an attachment from the cdn, failing with DiscordErr::TooLarge once
+    // more than max_size bytes have been seen. the bot token is not sent along
+    pub async fn download_attachment(
+        &self,
+        attachment: &Attachment,
+        max_size: u64,
+    ) -> Result<impl Stream<Item = Result<Vec<u8>, DiscordErr>>, DiscordErr> {
+        let too_large = move |size| DiscordErr::TooLarge {
+            size,
+            limit: max_size,
+        };
+        if attachment.size > max_size {
+            return Err(too_large(attachment.size));
+        }
+        let res = self.send(self.client.get(&attachment.url)).await?;
+        if let Some(len) = res.content_length().filter(|len| *len > max_size) {
+            return Err(too_large(len));
+        }
+
+        let chunks = res.bytes_stream().boxed();
+        Ok(stream::unfold(Some((chunks, 0)), move |state| async move {
+            let (mut chunks, read) = state?;
+            match chunks.next().await? {
+                Ok(chunk) => {
+                    let read = read + chunk.len() as u64;
+                    if read > max_size {
+                        Some((Err(too_large(read)), None))
+                    } else {
+                        Some((Ok(chunk.to_vec()), Some((chunks, read))))
+                    }
+                }
+                Err(e) => Some((Err(e.into()), None)),
+            }
+        }))
+    }
+
+    // writes an attachment to out, returning the number of bytes written
+    pub async fn download_attachment_to<W: AsyncWrite + Unpin>(
+        &self,
+        attachment: &Attachment,
+        max_size: u64,
+        out: &mut W,
+    ) -> Result<u64, DiscordErr> {
+        let chunks = self.download_attachment(attachment, max_size).await?;
+        futures::pin_mut!(chunks);
+        let mut written = 0;
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            out.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        out.flush().await?;
+        Ok(written)
+    }
+}
@@ -0,0 +1,659 @@
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+use reqwest::Method;
+use serde_json::Value;
+use tokio::io::{AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use super::Http;
+use crate::types::audit_logs::{AuditLog, AuditLogEntry, AuditLogEvent};
+use crate::types::snowflakes::Snowflake;
+use crate::DiscordErr;
+
+const MAX_LIMIT: u8 = 100; // the most entries the api returns per request
+const CSV_HEADER: &str =
+    "entry_id,action_type,action,user_id,target_id,reason,key,old_value,new_value,options\n";
+const POLL_INTERVAL: Duration = Duration::from_secs(10); // default time between polls
+const MAX_BACKOFF: Duration = Duration::from_secs(300); // default cap on the wait after failed polls
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    JsonLines, // one AuditLogEntry as json per line
+    Csv,       // one row per change, entries without changes get a single row
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct AuditLogQuery {
+    pub user_id: Option<Snowflake>,         // entries made by this user
+    pub action_type: Option<AuditLogEvent>, // entries of this action type
+    pub before: Option<Snowflake>,          // entries with an id lower than this one
+    pub after: Option<Snowflake>,           // entries with an id greater than this one
+    pub limit: Option<u8>,                  // entries per request (1-100), defaults to 50
+}
+
+impl AuditLogQuery {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(user_id) = self.user_id {
+            params.push(("user_id", user_id.to_string()));
+        }
+        if let Some(action_type) = self.action_type {
+            params.push(("action_type", action_type.value().to_string()));
+        }
+        if let Some(before) = self.before {
+            params.push(("before", before.to_string()));
+        }
+        if let Some(after) = self.after {
+            params.push(("after", after.to_string()));
+        }
+        if let Some(limit) = self.limit {
+            params.push(("limit", limit.clamp(1, MAX_LIMIT).to_string()));
+        }
+        params
+    }
+}
+
+impl Http {
+    pub async fn get_audit_log(
+        &self,
+        guild_id: Snowflake,
+        query: &AuditLogQuery,
+    ) -> Result<AuditLog, DiscordErr> {
+        let req = self
+            .request(Method::GET, &format!("/guilds/{}/audit-logs", guild_id))
+            .query(&query.params());
+        self.json(req).await
+    }
+
+    // walks back through history one page at a time, newest first, starting
+    // below query.before and stopping once query.after is reached
+    pub fn audit_log_pages(
+        &self,
+        guild_id: Snowflake,
+        query: AuditLogQuery,
+    ) -> impl Stream<Item = Result<AuditLog, DiscordErr>> + '_ {
+        stream::try_unfold(Some(query), move |query| async move {
+            let query = match query {
+                Some(query) => query,
+                None => return Ok(None),
+            };
+            let limit = query.limit.unwrap_or(MAX_LIMIT).clamp(1, MAX_LIMIT);
+            let request = AuditLogQuery {
+                after: None,
+                limit: Some(limit),
+                ..query.clone()
+            };
+
+            let mut page = self.get_audit_log(guild_id, &request).await?;
+            let oldest = page.audit_log_entries.iter().map(|e| e.id).min();
+            let oldest = match oldest {
+                Some(oldest) => oldest,
+                None => return Ok(None),
+            };
+
+            let mut exhausted = page.audit_log_entries.len() < limit as usize;
+            if let Some(after) = query.after {
+                page.audit_log_entries.retain(|e| e.id > after);
+                exhausted |= oldest <= after;
+            }
+            if page.audit_log_entries.is_empty() {
+                return Ok(None);
+            }
+
+            let next = if exhausted {
+                None
+            } else {
+                Some(AuditLogQuery {
+                    before: Some(oldest),
+                    ..query
+                })
+            };
+            Ok(Some((page, next)))
+        })
+    }
+
+    // appends every entry after `after` (the whole history when None) to out, oldest
+    // first, a page at a time. after each page is flushed the stream yields the id of
+    // the last entry written, so the latest one is where the next run resumes even if
+    // a later page fails. the csv header is written when out is empty. the stream ends
+    // after the last page or on the first error
+    pub fn export_audit_log<'a, W: AsyncWrite + AsyncSeek + Unpin + 'a>(
+        &'a self,
+        guild_id: Snowflake,
+        after: Option<Snowflake>,
+        format: ExportFormat,
+        out: &'a mut W,
+    ) -> impl Stream<Item = Result<Snowflake, DiscordErr>> + 'a {
+        stream::try_unfold(Some((out, after, true)), move |state| async move {
+            let (out, last, first) = match state {
+                Some(state) => state,
+                None => return Ok(None),
+            };
+            if first {
+                let len = out.seek(SeekFrom::End(0)).await?;
+                if format == ExportFormat::Csv && len == 0 {
+                    out.write_all(CSV_HEADER.as_bytes()).await?;
+                    out.flush().await?;
+                }
+            }
+
+            let query = AuditLogQuery {
+                after: Some(last.unwrap_or(0)),
+                limit: Some(MAX_LIMIT),
+                ..Default::default()
+            };
+            let mut entries = self
+                .get_audit_log(guild_id, &query)
+                .await?
+                .audit_log_entries;
+            let full = entries.len() == MAX_LIMIT as usize;
+            entries.sort_by_key(|e| e.id);
+            entries.retain(|e| last.is_none_or(|last| e.id > last));
+            let newest = match entries.last() {
+                Some(newest) => newest.id,
+                None => return Ok(None),
+            };
+
+            for entry in &entries {
+                let mut text = match format {
+                    ExportFormat::JsonLines => serde_json::to_string(entry)?,
+                    ExportFormat::Csv => csv_rows(entry)?,
+                };
+                text.push('\n');
+                out.write_all(text.as_bytes()).await?;
+            }
+            out.flush().await?;
+
+            let next = if full {
+                Some((out, Some(newest), false))
+            } else {
+                None
+            };
+            Ok(Some((newest, next)))
+        })
+    }
+
+    pub fn watch_audit_logs(&self) -> AuditLogWatcher<'_> {
+        AuditLogWatcher {
+            http: self,
+            guilds: Vec::new(),
+            interval: POLL_INTERVAL,
+            max_backoff: MAX_BACKOFF,
+            gateway: None,
+        }
+    }
+
+    // the entries of audit_log_pages; use the pages to resolve users, webhooks and the like
+    pub fn audit_log_entries(
+        &self,
+        guild_id: Snowflake,
+        query: AuditLogQuery,
+    ) -> impl Stream<Item = Result<AuditLogEntry, DiscordErr>> + '_ {
+        self.audit_log_pages(guild_id, query)
+            .map_ok(|page| stream::iter(page.audit_log_entries.into_iter().map(Ok)))
+            .try_flatten()
+    }
+}
+
+// yields new audit log entries of a set of guilds as they are made, polling
+// audit-logs?after= from the last entry seen in each guild; build one with
+// Http::watch_audit_logs
+pub struct AuditLogWatcher<'a> {
+    http: &'a Http,
+    guilds: Vec<(Snowflake, Option<Snowflake>)>, // each guild and the last entry id seen in it
+    interval: Duration,
+    max_backoff: Duration,
+    gateway: Option<BoxStream<'a, (Snowflake, AuditLogEntry)>>,
+}
+
+impl<'a> AuditLogWatcher<'a> {
+    // watches the guild from now on, skipping entries made before the first poll
+    pub fn guild(mut self, guild_id: Snowflake) -> Self {
+        self.guilds.push((guild_id, None));
+        self
+    }
+
+    // watches the guild starting after a known entry, e.g. one persisted by a previous run
+    pub fn guild_after(mut self, guild_id: Snowflake, last_seen: Snowflake) -> Self {
+        self.guilds.push((guild_id, Some(last_seen)));
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    // failed polls back off exponentially from the interval up to this
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    // GUILD_AUDIT_LOG_ENTRY_CREATE events as (guild id, entry); once set the watcher
+    // polls one last time to catch up, then only consumes these, falling back to
+    // polling if the stream ends
+    pub fn gateway(
+        mut self,
+        events: impl Stream<Item = (Snowflake, AuditLogEntry)> + Send + 'a,
+    ) -> Self {
+        self.gateway = Some(events.boxed());
+        self
+    }
+
+    // items are (guild id, entry), oldest first within each guild; errors do not end the stream
+    pub fn into_stream(
+        self,
+    ) -> impl Stream<Item = Result<(Snowflake, AuditLogEntry), DiscordErr>> + 'a {
+        let state = WatchState {
+            watcher: self,
+            queue: VecDeque::new(),
+            delay: None,
+            failures: 0,
+            caught_up: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(item) = state.queue.pop_front() {
+                    return Some((item, state));
+                }
+
+                if let (Some(events), true) = (&mut state.watcher.gateway, state.caught_up) {
+                    match events.next().await {
+                        Some((guild_id, entry)) => {
+                            if state.advance(guild_id, entry.id) {
+                                return Some((Ok((guild_id, entry)), state));
+                            }
+                        }
+                        None => state.watcher.gateway = None,
+                    }
+                    continue;
+                }
+
+                if let Some(delay) = state.delay {
+                    tokio::time::sleep(delay).await;
+                }
+                state.poll().await;
+            }
+        })
+    }
+}
+
+struct WatchState<'a> {
+    watcher: AuditLogWatcher<'a>,
+    queue: VecDeque<Result<(Snowflake, AuditLogEntry), DiscordErr>>,
+    delay: Option<Duration>, // wait before the next poll, None before the first one
+    failures: u32,           // consecutive polls that failed
+    caught_up: bool,         // a poll of every guild succeeded without a full page
+}
+
+impl WatchState<'_> {
+    // records entry_id as seen, returning false for unwatched guilds and old entries
+    fn advance(&mut self, guild_id: Snowflake, entry_id: Snowflake) -> bool {
+        match self
+            .watcher
+            .guilds
+            .iter_mut()
+            .find(|(id, _)| *id == guild_id)
+        {
+            Some((_, last)) if last.is_none_or(|last| entry_id > last) => {
+                *last = Some(entry_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    async fn poll(&mut self) {
+        let mut delay = self.watcher.interval;
+        let mut failed = false;
+        let mut limited = false;
+        let mut more = false;
+
+        for i in 0..self.watcher.guilds.len() {
+            let (guild_id, last) = self.watcher.guilds[i];
+            let query = match last {
+                Some(after) => AuditLogQuery {
+                    after: Some(after),
+                    limit: Some(MAX_LIMIT),
+                    ..Default::default()
+                },
+                None => AuditLogQuery {
+                    limit: Some(1),
+                    ..Default::default()
+                },
+            };
+
+            match self.watcher.http.get_audit_log(guild_id, &query).await {
+                Ok(page) => {
+                    let mut entries = page.audit_log_entries;
+                    entries.sort_by_key(|e| e.id);
+                    match last {
+                        // the first poll only finds where the log currently ends
+                        None => self.watcher.guilds[i].1 = Some(entries.last().map_or(0, |e| e.id)),
+                        Some(after) => {
+                            more |= entries.len() == MAX_LIMIT as usize;
+                            entries.retain(|e| e.id > after);
+                            if let Some(newest) = entries.last() {
+                                self.watcher.guilds[i].1 = Some(newest.id);
+                            }
+                            self.queue
+                                .extend(entries.into_iter().map(|e| Ok((guild_id, e))));
+                        }
+                    }
+                }
+                Err(DiscordErr::RateLimited { retry_after, .. }) => {
+                    limited = true;
+                    delay = delay.max(Duration::from_secs_f64(retry_after));
+                }
+                Err(e) => {
+                    failed = true;
+                    self.queue.push_back(Err(e));
+                }
+            }
+        }
+
+        if failed {
+            self.failures += 1;
+            let backoff = self
+                .watcher
+                .interval
+                .saturating_mul(1 << self.failures.min(16))
+                .min(self.watcher.max_backoff);
+            delay = delay.max(backoff);
+        } else {
+            self.failures = 0;
+        }
+        // the gateway only takes over once nothing is left to fetch
+        if !failed && !limited && !more {
+            self.caught_up = true;
+        }
+        // a full page means there is more to fetch right away
+        self.delay = Some(if more && !failed {
+            Duration::ZERO
+        } else {
+            delay
+        });
+    }
+}
+
+fn csv_rows(entry: &AuditLogEntry) -> Result<String, serde_json::Error> {
+    let id = |id: Option<Snowflake>| id.map(|id| id.to_string()).unwrap_or_default();
+    let options = match &entry.options {
+        Some(options) => serde_json::to_string(options)?,
+        None => String::new(),
+    };
+    let entry_fields = [
+        entry.id.to_string(),
+        serde_json::to_string(&entry.action_type)?,
+        format!("{:?}", entry.action_type),
+        id(entry.user_id),
+        id(entry.target_id),
+        entry.reason.clone().unwrap_or_default(),
+    ];
+
+    let mut changes = Vec::new();
+    for change in entry.changes.iter().flatten() {
+        // serializes to the api's {key, new_value, old_value}
+        let raw = serde_json::to_value(change)?;
+        changes.push([
+            csv_value(raw.get("key")),
+            csv_value(raw.get("old_value")),
+            csv_value(raw.get("new_value")),
+        ]);
+    }
+    if changes.is_empty() {
+        changes.push(Default::default());
+    }
+
+    let rows: Vec<String> = changes
+        .iter()
+        .map(|change| {
+            entry_fields
+                .iter()
+                .chain(change)
+                .chain([&options])
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect();
+    Ok(rows.join("\n"))
+}
+
+// strings as they are, anything else as json
+fn csv_value(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use std::io::Cursor;
+
+    use futures::stream::{self, StreamExt};
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::{AuditLogQuery, ExportFormat, CSV_HEADER};
+    use crate::http::Http;
+    use crate::types::audit_logs::AuditLogEntry;
+
+    fn entry(id: i64) -> serde_json::Value {
+        json!({ "id": id.to_string(), "action_type": 1 })
+    }
+
+    // serves audit-logs?before=&after=&limit= from entries 1..=backlog, failing the nth
+    // request. like the api, pages come newest first and hold the entries right after
+    // `after` when it is given, and the newest ones below `before` otherwise
+    async fn serve(listener: TcpListener, backlog: i64, fail: usize) {
+        let mut requests = 0;
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 8192];
+            let n = socket.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+            let query = request
+                .split(' ')
+                .nth(1)
+                .unwrap()
+                .split('?')
+                .nth(1)
+                .unwrap_or("");
+            let (mut before, mut after, mut limit) = (None::<i64>, None::<i64>, 50);
+            for (key, value) in query.split('&').filter_map(|kv| kv.split_once('=')) {
+                match key {
+                    "before" => before = Some(value.parse().unwrap()),
+                    "after" => after = Some(value.parse().unwrap()),
+                    "limit" => limit = value.parse().unwrap(),
+                    _ => {}
+                }
+            }
+
+            requests += 1;
+            let response = if requests == fail {
+                "HTTP/1.1 500 Internal Server Error\r\ncontent-length: 2\r\n\r\n{}".to_string()
+            } else {
+                let below = before.unwrap_or(backlog + 1).min(backlog + 1);
+                let mut ids: Vec<i64> = match after {
+                    Some(after) => (after + 1..below).take(limit).collect(),
+                    None => (1..below).rev().take(limit).collect(),
+                };
+                ids.sort_by(|a, b| b.cmp(a));
+                let entries: Vec<_> = ids.into_iter().map(entry).collect();
+                let body = json!({ "audit_log_entries": entries }).to_string();
+                format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            };
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn watcher_catches_up_on_a_long_backlog_before_using_the_gateway() {
+        let http = mock(250, 1).await;
+
+        let live: Vec<(i64, AuditLogEntry)> = (251..=252)
+            .map(|id| (7, serde_json::from_value(entry(id)).unwrap()))
+            .collect();
+        let gateway = stream::iter(live).chain(stream::pending());
+        let watcher = http
+            .watch_audit_logs()
+            .guild_after(7, 0)
+            .interval(Duration::from_millis(5))
+            .max_backoff(Duration::from_millis(20))
+            .gateway(gateway);
+
+        let items: Vec<_> = tokio::time::timeout(
+            Duration::from_secs(5),
+            watcher.into_stream().take(253).collect::<Vec<_>>(),
+        )
+        .await
+        .expect("the watcher stopped before yielding every entry");
+
+        assert!(items[0].is_err(), "the failed poll is reported");
+        let ids: Vec<i64> = items[1..]
+            .iter()
+            .map(|item| item.as_ref().unwrap().1.id)
+            .collect();
+        assert_eq!(ids, (1..=252).collect::<Vec<_>>());
+    }
+
+    async fn mock(backlog: i64, fail: usize) -> Http {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, backlog, fail));
+        Http::with_base_url("token", base)
+    }
+
+    async fn page_ids(http: &Http, query: AuditLogQuery) -> Vec<Vec<i64>> {
+        http.audit_log_pages(7, query)
+            .map(|page| {
+                let page = page.expect("a page failed");
+                page.audit_log_entries.iter().map(|e| e.id).collect()
+            })
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn pages_walk_back_from_before_without_repeating_entries() {
+        let http = mock(250, 0).await;
+        let query = AuditLogQuery {
+            before: Some(101),
+            limit: Some(30),
+            ..Default::default()
+        };
+
+        let pages = page_ids(&http, query).await;
+        assert_eq!(
+            pages.iter().map(Vec::len).collect::<Vec<_>>(),
+            [30, 30, 30, 10]
+        );
+        let ids: Vec<i64> = pages.into_iter().flatten().collect();
+        assert_eq!(ids, (1..=100).rev().collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn paging_stops_after_a_short_page() {
+        // a fourth request would fail
+        let http = mock(250, 4).await;
+
+        let pages = page_ids(&http, AuditLogQuery::default()).await;
+        assert_eq!(
+            pages.iter().map(Vec::len).collect::<Vec<_>>(),
+            [100, 100, 50]
+        );
+        let ids: Vec<i64> = pages.into_iter().flatten().collect();
+        assert_eq!(ids, (1..=250).rev().collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn paging_stops_on_an_empty_page() {
+        let http = mock(200, 4).await;
+
+        let pages = page_ids(&http, AuditLogQuery::default()).await;
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [100, 100]);
+
+        let http = mock(0, 2).await;
+        assert!(page_ids(&http, AuditLogQuery::default()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn paging_stops_once_after_is_reached() {
+        let http = mock(250, 4).await;
+        let query = AuditLogQuery {
+            after: Some(120),
+            ..Default::default()
+        };
+
+        let pages = page_ids(&http, query).await;
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [100, 30]);
+        let ids: Vec<i64> = pages.into_iter().flatten().collect();
+        assert_eq!(ids, (121..=250).rev().collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn export_reports_progress_before_a_failed_page() {
+        let http = mock(250, 3).await;
+        let mut out = Cursor::new(Vec::new());
+
+        let progress: Vec<_> = http
+            .export_audit_log(7, None, ExportFormat::JsonLines, &mut out)
+            .collect()
+            .await;
+        assert_eq!(progress.len(), 3);
+        assert_eq!(progress[0].as_ref().unwrap(), &100);
+        assert_eq!(progress[1].as_ref().unwrap(), &200);
+        assert!(progress[2].is_err());
+
+        let resume = *progress[1].as_ref().unwrap();
+        let progress: Vec<_> = http
+            .export_audit_log(7, Some(resume), ExportFormat::JsonLines, &mut out)
+            .collect()
+            .await;
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0].as_ref().unwrap(), &250);
+
+        let ids: Vec<i64> = String::from_utf8(out.into_inner())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<AuditLogEntry>(line).unwrap().id)
+            .collect();
+        assert_eq!(ids, (1..=250).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn csv_export_writes_the_header_only_into_an_empty_output() {
+        let http = mock(0, 0).await;
+        let mut out = Cursor::new(Vec::new());
+
+        for _ in 0..2 {
+            let progress: Vec<_> = http
+                .export_audit_log(7, None, ExportFormat::Csv, &mut out)
+                .collect()
+                .await;
+            assert!(progress.is_empty());
+        }
+        assert_eq!(String::from_utf8(out.into_inner()).unwrap(), CSV_HEADER);
+    }
+}
@@ -0,0 +1,507 @@
+use reqwest::Method;
+use serde::Serialize;
+
+use super::{nullable_id, with_reason, Http};
+use crate::types::channels::{Channel, ChannelType, ForumTag};
+use crate::types::permissions::overwrites::ChannelOverwrite;
+use crate::types::snowflakes::{self, Snowflake};
+use crate::DiscordErr;
+
+// a new channel for Http::create_guild_channel, which needs at least a name, or
+// changes to one for Http::modify_channel; only the fields that were set are sent
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct EditChannel {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    channel_type: Option<ChannelType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    topic: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nsfw: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rate_limit_per_user: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bitrate: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_limit: Option<i32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "nullable_id"
+    )]
+    parent_id: Option<Option<Snowflake>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    permission_overwrites: Option<Vec<ChannelOverwrite>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rtc_region: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_auto_archive_duration: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    available_tags: Option<Vec<EditForumTag>>,
+    #[serde(skip)]
+    reason: Option<String>,
+}
+
+impl EditChannel {
+    pub fn new() -> Self {
+        EditChannel::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    // set on creation; text and announcement channels can be converted into each other
+    pub fn kind(mut self, channel_type: ChannelType) -> Self {
+        self.channel_type = Some(channel_type);
+        self
+    }
+
+    pub fn topic(mut self, topic: Option<&str>) -> Self {
+        self.topic = Some(topic.map(String::from));
+        self
+    }
+
+    pub fn nsfw(mut self, nsfw: bool) -> Self {
+        self.nsfw = Some(nsfw);
+        self
+    }
+
+    // slowmode in seconds (0-21600)
+    pub fn rate_limit_per_user(mut self, seconds: i32) -> Self {
+        self.rate_limit_per_user = Some(seconds);
+        self
+    }
+
+    pub fn bitrate(mut self, bitrate: i32) -> Self {
+        self.bitrate = Some(bitrate);
+        self
+    }
+
+    // 0 for no limit
+    pub fn user_limit(mut self, limit: i32) -> Self {
+        self.user_limit = Some(limit);
+        self
+    }
+
+    // None moves the channel out of its category
+    pub fn parent(mut self, category_id: Option<Snowflake>) -> Self {
+        self.parent_id = Some(category_id);
+        self
+    }
+
+    pub fn position(mut self, position: i32) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    // replaces all overwrites; use Http::edit_channel_permissions to change one
+    pub fn permission_overwrites(mut self, overwrites: Vec<ChannelOverwrite>) -> Self {
+        self.permission_overwrites = Some(overwrites);
+        self
+    }
+
+    // None picks the voice region automatically
+    pub fn rtc_region(mut self, region: Option<&str>) -> Self {
+        self.rtc_region = Some(region.map(String::from));
+        self
+    }
+
+    // minutes: 60, 1440, 4320 or 10080
+    pub fn default_auto_archive_duration(mut self, minutes: i32) -> Self {
+        self.default_auto_archive_duration = Some(minutes);
+        self
+    }
+
+    // replaces the forum's tags; tags without an id are created
+    pub fn available_tags(mut self, tags: Vec<EditForumTag>) -> Self {
+        self.available_tags = Some(tags);
+        self
+    }
+
+    // shown in the guild's audit log
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EditForumTag {
+    #[serde(with = "snowflakes::option", skip_serializing_if = "Option::is_none")]
+    pub id: Option<Snowflake>, // the tag to keep or change, None for a new tag
+    pub name: String,    // the name of the tag (0-20 characters)
+    pub moderated: bool, // whether only members with MANAGE_THREADS can apply it
+    #[serde(with = "snowflakes::option")]
+    pub emoji_id: Option<Snowflake>, // the id of a guild's custom emoji
+    pub emoji_name: Option<String>, // the unicode character of the emoji
+}
+
+impl EditForumTag {
+    pub fn new(name: impl Into<String>) -> Self {
+        EditForumTag {
+            id: None,
+            name: name.into(),
+            moderated: false,
+            emoji_id: None,
+            emoji_name: None,
+        }
+    }
+}
+
+impl From<&ForumTag> for EditForumTag {
+    fn from(tag: &ForumTag) -> Self {
+        EditForumTag {
+            id: Some(tag.id),
+            name: tag.name.clone(),
+            moderated: tag.moderated,
+            emoji_id: tag.emoji_id,
+            emoji_name: tag.emoji_name.clone(),
+        }
+    }
+}
+
+// one entry of a bulk channel reorder
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct ChannelPosition {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // channel id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i32>, // sorting position of the channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_permissions: Option<bool>, // syncs the permission overwrites with the new parent, if moving to a new category
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "nullable_id"
+    )]
+    pub parent_id: Option<Option<Snowflake>>, // the new parent category for the channel, Some(None) for none
+}
+
+// the client lists categories first by position, then within each category text-like
+// channels above voice channels; positions only compare within these groups
+fn sort_group(channel_type: ChannelType) -> u8 {
+    match channel_type {
+        ChannelType::GuildCategory => 2,
+        ChannelType::GuildVoice | ChannelType::GuildStageVoice => 1,
+        _ => 0,
+    }
+}
+
+impl ChannelPosition {
+    // the smallest reorder payload that moves channel_id to `index` among the
+    // channels of its kind under `parent_id` (None for the top level, which is
+    // also where categories live). new positions are worked out from the ones
+    // the neighbours already have, so only channels whose place in the listing
+    // has to change are sent. returns nothing when the channel is not in `channels`
+    pub fn for_move(
+        channels: &[Channel],
+        channel_id: Snowflake,
+        parent_id: Option<Snowflake>,
+        index: usize,
+    ) -> Vec<ChannelPosition> {
+        let moving = match channels.iter().find(|c| c.id == channel_id) {
+            Some(channel) => channel,
+            None => return Vec::new(),
+        };
+        let group = sort_group(moving.channel_type);
+
+        // (position, id) sorts the way the client lists channels
+        let mut siblings: Vec<(i32, Snowflake)> = channels
+            .iter()
+            .filter(|c| c.id != channel_id)
+            .filter(|c| c.parent_id == parent_id && sort_group(c.channel_type) == group)
+            .filter_map(|c| Some((c.position?, c.id)))
+            .collect();
+        siblings.sort();
+        let (before, after) = siblings.split_at(index.min(siblings.len()));
+
+        let parent_changed = moving.parent_id != parent_id;
+        let mut moves = place(before, channel_id, moving.position, after);
+        moves.sort();
+        moves
+            .into_iter()
+            .filter(|&(position, id)| {
+                id != channel_id || parent_changed || moving.position != Some(position)
+            })
+            .map(|(position, id)| {
+                let moved = id == channel_id && parent_changed;
+                ChannelPosition {
+                    id,
+                    position: Some(position),
+                    lock_permissions: None,
+                    parent_id: if moved { Some(parent_id) } else { None },
+                }
+            })
+            .collect()
+    }
+}
+
+// a position for channel `id` between the sorted `before` and `after`, along with
+// the fewest neighbours that have to be pushed aside to make room, as (position, id)
+fn place(
+    before: &[(i32, Snowflake)],
+    id: Snowflake,
+    current: Option<i32>,
+    after: &[(i32, Snowflake)],
+) -> Vec<(i32, Snowflake)> {
+    let fits = |key: (i32, Snowflake)| {
+        before.last().is_none_or(|prev| *prev < key) && after.first().is_none_or(|next| key < *next)
+    };
+    if let Some(current) = current.filter(|&p| fits((p, id))) {
+        return vec![(current, id)];
+    }
+
+    // right after the previous channel, pushing the ones below further down
+    let start = match before.last() {
+        Some(&(p, prev)) if id > prev => p,
+        Some(&(p, _)) => p + 1,
+        None => 0,
+    };
+    let mut down = vec![(start, id)];
+    for &(p, other) in after {
+        let (last, last_id) = down[down.len() - 1];
+        if (last, last_id) < (p, other) {
+            break;
+        }
+        down.push((if other > last_id { last } else { last + 1 }, other));
+    }
+
+    // right before the next channel, pushing the ones above further up, as long
+    // as no position has to drop below 0
+    let up = after.first().and_then(|&(p, next)| {
+        let mut up = vec![(if id < next { p } else { p - 1 }, id)];
+        for &(p, other) in before.iter().rev() {
+            let (first, first_id) = up[up.len() - 1];
+            if (p, other) < (first, first_id) {
+                break;
+            }
+            up.push((if other < first_id { first } else { first - 1 }, other));
+        }
+        up.iter().all(|&(p, _)| p >= 0).then_some(up)
+    });
+
+    let cost = |moves: &[(i32, Snowflake)]| {
+        moves.len() - usize::from(moves.iter().any(|&m| Some(m) == current.map(|p| (p, id))))
+    };
+    match up {
+        Some(up) if cost(&up) < cost(&down) => up,
+        _ => down,
+    }
+}
+
+impl Http {
+    pub async fn get_channel(&self, channel_id: Snowflake) -> Result<Channel, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/channels/{}", channel_id));
+        self.json(req).await
+    }
+
+    pub async fn get_guild_channels(
+        &self,
+        guild_id: Snowflake,
+    ) -> Result<Vec<Channel>, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/guilds/{}/channels", guild_id));
+        self.json(req).await
+    }
+
+    pub async fn create_guild_channel(
+        &self,
+        guild_id: Snowflake,
+        channel: &EditChannel,
+    ) -> Result<Channel, DiscordErr> {
+        let req = self
+            .request(Method::POST, &format!("/guilds/{}/channels", guild_id))
+            .json(channel);
+        self.json(with_reason(req, channel.reason.as_deref())).await
+    }
+
+    pub async fn modify_channel(
+        &self,
+        channel_id: Snowflake,
+        edit: &EditChannel,
+    ) -> Result<Channel, DiscordErr> {
+        let req = self
+            .request(Method::PATCH, &format!("/channels/{}", channel_id))
+            .json(edit);
+        self.json(with_reason(req, edit.reason.as_deref())).await
+    }
+
+    // returns the deleted channel
+    pub async fn delete_channel(
+        &self,
+        channel_id: Snowflake,
+        reason: Option<&str>,
+    ) -> Result<Channel, DiscordErr> {
+        let req = self.request(Method::DELETE, &format!("/channels/{}", channel_id));
+        self.json(with_reason(req, reason)).await
+    }
+
+    // creates or replaces the overwrite for overwrite.id
+    pub async fn edit_channel_permissions(
+        &self,
+        channel_id: Snowflake,
+        overwrite: &ChannelOverwrite,
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("/channels/{}/permissions/{}", channel_id, overwrite.id);
+        let req = self.request(Method::PUT, &path).json(overwrite);
+        self.empty(with_reason(req, reason)).await
+    }
+
+    pub async fn delete_channel_permission(
+        &self,
+        channel_id: Snowflake,
+        overwrite_id: Snowflake,
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("/channels/{}/permissions/{}", channel_id, overwrite_id);
+        let req = self.request(Method::DELETE, &path);
+        self.empty(with_reason(req, reason)).await
+    }
+
+    // see ChannelPosition::for_move for building the payload
+    pub async fn modify_guild_channel_positions(
+        &self,
+        guild_id: Snowflake,
+        positions: &[ChannelPosition],
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let req = self
+            .request(Method::PATCH, &format!("/guilds/{}/channels", guild_id))
+            .json(positions);
+        self.empty(with_reason(req, reason)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ChannelPosition;
+    use crate::types::channels::Channel;
+    use crate::types::snowflakes::Snowflake;
+
+    fn channel(id: Snowflake, kind: u8, parent_id: Option<Snowflake>, position: i32) -> Channel {
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "type": kind,
+            "parent_id": parent_id.map(|id| id.to_string()),
+            "position": position,
+        }))
+        .unwrap()
+    }
+
+    fn text(ids_and_positions: &[(Snowflake, i32)]) -> Vec<Channel> {
+        ids_and_positions
+            .iter()
+            .map(|&(id, position)| channel(id, 0, None, position))
+            .collect()
+    }
+
+    // the ids under parent_id in the order the client lists them once the payload is applied
+    fn listing(
+        channels: &[Channel],
+        payload: &[ChannelPosition],
+        parent_id: Option<Snowflake>,
+    ) -> Vec<Snowflake> {
+        let mut listed: Vec<(i32, Snowflake)> = channels
+            .iter()
+            .map(|c| match payload.iter().find(|p| p.id == c.id) {
+                Some(p) => (
+                    p.position.unwrap(),
+                    c.id,
+                    p.parent_id.unwrap_or(c.parent_id),
+                ),
+                None => (c.position.unwrap(), c.id, c.parent_id),
+            })
+            .filter(|&(_, _, parent)| parent == parent_id)
+            .map(|(position, id, _)| (position, id))
+            .collect();
+        listed.sort();
+        listed.into_iter().map(|(_, id)| id).collect()
+    }
+
+    fn ids(payload: &[ChannelPosition]) -> Vec<Snowflake> {
+        payload.iter().map(|p| p.id).collect()
+    }
+
+    #[test]
+    fn moving_down_in_contiguous_positions() {
+        let channels = text(&[(10, 0), (11, 1), (12, 2), (13, 3)]);
+        let payload = ChannelPosition::for_move(&channels, 10, None, 2);
+        assert_eq!(listing(&channels, &payload, None), [11, 12, 10, 13]);
+        assert_eq!(ids(&payload), [10]);
+    }
+
+    #[test]
+    fn moving_up_in_contiguous_positions() {
+        let channels = text(&[(10, 0), (11, 1), (12, 2), (13, 3)]);
+        let payload = ChannelPosition::for_move(&channels, 13, None, 0);
+        assert_eq!(listing(&channels, &payload, None), [13, 10, 11, 12]);
+        assert_eq!(ids(&payload), [13, 10]);
+
+        let payload = ChannelPosition::for_move(&channels, 12, None, 1);
+        assert_eq!(listing(&channels, &payload, None), [10, 12, 11, 13]);
+        assert_eq!(payload.len(), 1);
+    }
+
+    #[test]
+    fn moving_with_gaps_and_offsets_only_sends_the_channel() {
+        let channels = text(&[(10, 0), (11, 5), (12, 10), (13, 15)]);
+        let payload = ChannelPosition::for_move(&channels, 13, None, 1);
+        assert_eq!(listing(&channels, &payload, None), [10, 13, 11, 12]);
+        assert_eq!(ids(&payload), [13]);
+
+        let channels = text(&[(10, 3), (11, 4), (12, 5), (13, 6)]);
+        let payload = ChannelPosition::for_move(&channels, 13, None, 0);
+        assert_eq!(listing(&channels, &payload, None), [13, 10, 11, 12]);
+        assert_eq!(ids(&payload), [13]);
+    }
+
+    #[test]
+    fn a_channel_already_in_place_is_not_sent() {
+        let channels = text(&[(10, 0), (11, 5), (12, 10)]);
+        assert!(ChannelPosition::for_move(&channels, 11, None, 1).is_empty());
+    }
+
+    #[test]
+    fn changing_parent_sends_the_new_parent() {
+        let channels = vec![
+            channel(100, 4, None, 0),
+            channel(200, 4, None, 1),
+            channel(20, 0, Some(100), 0),
+            channel(30, 0, Some(200), 0),
+            channel(31, 0, Some(200), 1),
+        ];
+        let payload = ChannelPosition::for_move(&channels, 20, Some(200), 1);
+        assert_eq!(listing(&channels, &payload, Some(200)), [30, 20, 31]);
+        assert_eq!(ids(&payload), [20]);
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!([{ "id": "20", "position": 1, "parent_id": "200" }])
+        );
+
+        // out of every category, even when the position stays the same
+        let payload = ChannelPosition::for_move(&channels, 30, None, 0);
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!([{ "id": "30", "position": 0, "parent_id": null }])
+        );
+    }
+
+    #[test]
+    fn an_index_past_the_end_moves_to_the_bottom() {
+        let channels = text(&[(10, 0), (11, 1), (12, 2)]);
+        let payload = ChannelPosition::for_move(&channels, 10, None, 99);
+        assert_eq!(listing(&channels, &payload, None), [11, 12, 10]);
+        assert_eq!(ids(&payload), [10]);
+    }
+
+    #[test]
+    fn an_unknown_channel_gives_an_empty_payload() {
+        let channels = text(&[(10, 0), (11, 1)]);
+        assert!(ChannelPosition::for_move(&channels, 99, None, 0).is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{nullable_id, with_reason, Http};
+use crate::types::cdn::{self, ImageFormat};
+use crate::types::guilds::{
+    DefaultMessageNotificationLevel, ExplicitContentFilterLevel, Guild, GuildFeature, GuildPreview,
+    GuildWidgetSettings, Integration, SystemChannelFlags, VanityUrl, VerificationLevel,
+    VoiceRegion,
+};
+use crate::types::snowflakes::Snowflake;
+use crate::DiscordErr;
+
+const DEFAULT_PRUNE_DAYS: u8 = 7; // days of inactivity the api prunes by when none are given
+
+// changes to a guild for Http::modify_guild; only the fields that were set are sent
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct EditGuild {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification_level: Option<VerificationLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_message_notifications: Option<DefaultMessageNotificationLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explicit_content_filter: Option<ExplicitContentFilterLevel>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "nullable_id"
+    )]
+    afk_channel_id: Option<Option<Snowflake>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    afk_timeout: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    banner: Option<Option<String>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "nullable_id"
+    )]
+    owner_id: Option<Option<Snowflake>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "nullable_id"
+    )]
+    system_channel_id: Option<Option<Snowflake>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_channel_flags: Option<SystemChannelFlags>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "nullable_id"
+    )]
+    rules_channel_id: Option<Option<Snowflake>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "nullable_id"
+    )]
+    public_updates_channel_id: Option<Option<Snowflake>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preferred_locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    features: Option<Vec<GuildFeature>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    premium_progress_bar_enabled: Option<bool>,
+    #[serde(skip)]
+    reason: Option<String>,
+}
+
+impl EditGuild {
+    pub fn new() -> Self {
+        EditGuild::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    // None lets discord pick the region automatically
+    pub fn region(mut self, region: Option<&str>) -> Self {
+        self.region = Some(region.map(String::from));
+        self
+    }
+
+    pub fn verification_level(mut self, level: VerificationLevel) -> Self {
+        self.verification_level = Some(level);
+        self
+    }
+
+    pub fn default_message_notifications(mut self, level: DefaultMessageNotificationLevel) -> Self {
+        self.default_message_notifications = Some(level);
+        self
+    }
+
+    pub fn explicit_content_filter(mut self, level: ExplicitContentFilterLevel) -> Self {
+        self.explicit_content_filter = Some(level);
+        self
+    }
+
+    pub fn afk_channel(mut self, channel_id: Option<Snowflake>) -> Self {
+        self.afk_channel_id = Some(channel_id);
+        self
+    }
+
+    // seconds before idle members are moved to the afk channel
+    pub fn afk_timeout(mut self, seconds: i32) -> Self {
+        self.afk_timeout = Some(seconds);
+        self
+    }
+
+    pub fn icon(mut self, image: &[u8], format: ImageFormat) -> Self {
+        self.icon = Some(Some(cdn::data_uri(image, format)));
+        self
+    }
+
+    pub fn remove_icon(mut self) -> Self {
+        self.icon = Some(None);
+        self
+    }
+
+    pub fn banner(mut self, image: &[u8], format: ImageFormat) -> Self {
+        self.banner = Some(Some(cdn::data_uri(image, format)));
+        self
+    }
+
+    pub fn remove_banner(mut self) -> Self {
+        self.banner = Some(None);
+        self
+    }
+
+    // transfers ownership; the bot must own the guild
+    pub fn owner(mut self, user_id: Snowflake) -> Self {
+        self.owner_id = Some(Some(user_id));
+        self
+    }
+
+    pub fn system_channel(mut self, channel_id: Option<Snowflake>) -> Self {
+        self.system_channel_id = Some(channel_id);
+        self
+    }
+
+    pub fn system_channel_flags(mut self, flags: SystemChannelFlags) -> Self {
+        self.system_channel_flags = Some(flags);
+        self
+    }
+
+    pub fn rules_channel(mut self, channel_id: Option<Snowflake>) -> Self {
+        self.rules_channel_id = Some(channel_id);
+        self
+    }
+
+    pub fn public_updates_channel(mut self, channel_id: Option<Snowflake>) -> Self {
+        self.public_updates_channel_id = Some(channel_id);
+        self
+    }
+
+    pub fn preferred_locale(mut self, locale: impl Into<String>) -> Self {
+        self.preferred_locale = Some(locale.into());
+        self
+    }
+
+    // replaces the whole list; only some features, such as COMMUNITY, can be toggled
+    pub fn features(mut self, features: Vec<GuildFeature>) -> Self {
+        self.features = Some(features);
+        self
+    }
+
+    pub fn description(mut self, description: Option<&str>) -> Self {
+        self.description = Some(description.map(String::from));
+        self
+    }
+
+    pub fn premium_progress_bar_enabled(mut self, enabled: bool) -> Self {
+        self.premium_progress_bar_enabled = Some(enabled);
+        self
+    }
+
+    // shown in the guild's audit log
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct Pruned {
+    pruned: Option<u32>,
+}
+
+impl Http {
+    // with_counts fills in approximate_member_count and approximate_presence_count
+    pub async fn get_guild(
+        &self,
+        guild_id: Snowflake,
+        with_counts: bool,
+    ) -> Result<Guild, DiscordErr> {
+        let req = self
+            .request(Method::GET, &format!("/guilds/{}", guild_id))
+            .query(&[("with_counts", with_counts)]);
+        self.json(req).await
+    }
+
+    pub async fn modify_guild(
+        &self,
+        guild_id: Snowflake,
+        edit: &EditGuild,
+    ) -> Result<Guild, DiscordErr> {
+        let req = self
+            .request(Method::PATCH, &format!("/guilds/{}", guild_id))
+            .json(edit);
+        self.json(with_reason(req, edit.reason.as_deref())).await
+    }
+
+    pub async fn get_guild_preview(&self, guild_id: Snowflake) -> Result<GuildPreview, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/guilds/{}/preview", guild_id));
+        self.json(req).await
+    }
+
+    pub async fn get_guild_widget_settings(
+        &self,
+        guild_id: Snowflake,
+    ) -> Result<GuildWidgetSettings, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/guilds/{}/widget", guild_id));
+        self.json(req).await
+    }
+
+    pub async fn modify_guild_widget(
+        &self,
+        guild_id: Snowflake,
+        settings: &GuildWidgetSettings,
+        reason: Option<&str>,
+    ) -> Result<GuildWidgetSettings, DiscordErr> {
+        let req = self
+            .request(Method::PATCH, &format!("/guilds/{}/widget", guild_id))
+            .json(settings);
+        self.json(with_reason(req, reason)).await
+    }
+
+    pub async fn get_guild_vanity_url(&self, guild_id: Snowflake) -> Result<VanityUrl, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/guilds/{}/vanity-url", guild_id));
+        self.json(req).await
+    }
+
+    // how many members a prune would kick; members with roles are only counted
+    // when all of their roles are in include_roles
+    pub async fn get_guild_prune_count(
+        &self,
+        guild_id: Snowflake,
+        days: Option<u8>,
+        include_roles: &[Snowflake],
+    ) -> Result<u32, DiscordErr> {
+        let mut query = vec![("days", prune_days(days).to_string())];
+        if !include_roles.is_empty() {
+            let roles: Vec<String> = include_roles.iter().map(|id| id.to_string()).collect();
+            query.push(("include_roles", roles.join(",")));
+        }
+        let req = self
+            .request(Method::GET, &format!("/guilds/{}/prune", guild_id))
+            .query(&query);
+        let pruned: Pruned = self.json(req).await?;
+        Ok(pruned.pruned.unwrap_or(0))
+    }
+
+    // kicks members inactive for `days`; the count is only returned when
+    // compute_prune_count is set, which discord advises against for large guilds
+    pub async fn begin_guild_prune(
+        &self,
+        guild_id: Snowflake,
+        days: Option<u8>,
+        include_roles: &[Snowflake],
+        compute_prune_count: bool,
+        reason: Option<&str>,
+    ) -> Result<Option<u32>, DiscordErr> {
+        let roles: Vec<String> = include_roles.iter().map(|id| id.to_string()).collect();
+        let req = self
+            .request(Method::POST, &format!("/guilds/{}/prune", guild_id))
+            .json(&json!({
+                "days": prune_days(days),
+                "compute_prune_count": compute_prune_count,
+                "include_roles": roles,
+            }));
+        let pruned: Pruned = self.json(with_reason(req, reason)).await?;
+        Ok(pruned.pruned)
+    }
+
+    pub async fn get_guild_voice_regions(
+        &self,
+        guild_id: Snowflake,
+    ) -> Result<Vec<VoiceRegion>, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/guilds/{}/regions", guild_id));
+        self.json(req).await
+    }
+
+    pub async fn get_guild_integrations(
+        &self,
+        guild_id: Snowflake,
+    ) -> Result<Vec<Integration>, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/guilds/{}/integrations", guild_id));
+        self.json(req).await
+    }
+
+    pub async fn delete_guild_integration(
+        &self,
+        guild_id: Snowflake,
+        integration_id: Snowflake,
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("/guilds/{}/integrations/{}", guild_id, integration_id);
+        let req = self.request(Method::DELETE, &path);
+        self.empty(with_reason(req, reason)).await
+    }
+}
+
+// the api takes 1 to 30 days
+fn prune_days(days: Option<u8>) -> u8 {
+    days.unwrap_or(DEFAULT_PRUNE_DAYS).clamp(1, 30)
+}
@@ -0,0 +1,292 @@
+use std::cmp::Reverse;
+
+use futures::stream::{self, Stream, TryStreamExt};
+
+use super::messages::{MessageQuery, MAX_LIMIT};
+use super::Http;
+use crate::types::messages::Message;
+use crate::types::snowflakes::{self, Snowflake};
+use crate::types::DiscordTimestamp;
+use crate::DiscordErr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Direction {
+    #[default]
+    NewestFirst, // walks back from the end of the range
+    OldestFirst, // walks forward from the start of the range
+}
+
+// a time range of channel history; either end may be left open
+#[derive(Clone, Default, Debug)]
+pub struct HistoryRange {
+    pub since: Option<DiscordTimestamp>, // messages sent at or after this time
+    pub until: Option<DiscordTimestamp>, // messages sent before this time
+    pub direction: Direction,
+}
+
+impl HistoryRange {
+    pub fn between(since: DiscordTimestamp, until: DiscordTimestamp) -> Self {
+        HistoryRange {
+            since: Some(since),
+            until: Some(until),
+            direction: Direction::default(),
+        }
+    }
+
+    pub fn oldest_first(mut self) -> Self {
+        self.direction = Direction::OldestFirst;
+        self
+    }
+
+    // the range as exclusive snowflake bounds: the lowest id a message sent at
+    // `since` could have, minus one, and the lowest id at `until`. a `since` at or
+    // before the discord epoch leaves the start open, as no id is lower than 0
+    fn bounds(&self) -> (Option<Snowflake>, Option<Snowflake>) {
+        let id = |t: DiscordTimestamp| snowflakes::new(t.naive_utc()).max(0);
+        let after = self.since.map(id).filter(|&id| id > 0).map(|id| id - 1);
+        (after, self.until.map(id))
+    }
+}
+
+impl Http {
+    // pages of messages in the range, each sorted in the range's direction
+    pub fn message_history_pages(
+        &self,
+        channel_id: Snowflake,
+        range: HistoryRange,
+    ) -> impl Stream<Item = Result<Vec<Message>, DiscordErr>> + '_ {
+        let (after, before) = range.bounds();
+        let cursor = match range.direction {
+            Direction::NewestFirst => before,
+            Direction::OldestFirst => Some(after.unwrap_or(0)),
+        };
+
+        stream::try_unfold(Some(cursor), move |cursor| async move {
+            let cursor = match cursor {
+                Some(cursor) => cursor,
+                None => return Ok(None),
+            };
+            let query = match range.direction {
+                Direction::NewestFirst => MessageQuery {
+                    before: cursor,
+                    ..MessageQuery::default()
+                },
+                Direction::OldestFirst => MessageQuery {
+                    after: cursor,
+                    ..MessageQuery::default()
+                },
+            };
+            let mut page = self
+                .get_channel_messages(
+                    channel_id,
+                    &MessageQuery {
+                        limit: Some(MAX_LIMIT),
+                        ..query
+                    },
+                )
+                .await?;
+            let exhausted = page.len() < MAX_LIMIT as usize;
+
+            match range.direction {
+                Direction::NewestFirst => page.sort_by_key(|m| Reverse(m.id)),
+                Direction::OldestFirst => page.sort_by_key(|m| m.id),
+            }
+            let next = page.last().map(|m| m.id);
+            let len = page.len();
+            page.retain(|m| after.is_none_or(|a| m.id > a) && before.is_none_or(|b| m.id < b));
+            if page.is_empty() {
+                return Ok(None);
+            }
+
+            // a message was dropped at the far end of the page, so the range is done
+            let done = exhausted || page.len() < len;
+            let next = if done { None } else { Some(next) };
+            Ok(Some((page, next)))
+        })
+    }
+
+    // every message in the range, fetching pages as the stream is polled;
+    // "all of last tuesday" is HistoryRange::between(tuesday, wednesday)
+    pub fn message_history(
+        &self,
+        channel_id: Snowflake,
+        range: HistoryRange,
+    ) -> impl Stream<Item = Result<Message, DiscordErr>> + '_ {
+        self.message_history_pages(channel_id, range)
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{DateTime, Duration, NaiveDate, Utc};
+    use futures::stream::StreamExt;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::{Direction, HistoryRange};
+    use crate::http::Http;
+    use crate::types::snowflakes::{self, Snowflake};
+    use crate::types::DiscordTimestamp;
+
+    fn midnight(year: i32, month: u32, day: u32) -> DiscordTimestamp {
+        let date = NaiveDate::from_ymd(year, month, day).and_hms_milli(0, 0, 0, 0);
+        DateTime::from_utc(date, Utc)
+    }
+
+    fn at(minute: i64) -> DiscordTimestamp {
+        midnight(2023, 5, 1) + Duration::minutes(minute)
+    }
+
+    fn id(minute: i64) -> Snowflake {
+        snowflakes::new(at(minute).naive_utc())
+    }
+
+    // serves channels/{}/messages?before=&after=&limit= from one message a minute
+    // for `minutes` minutes, recording the query of each request. like the api,
+    // pages come newest first and hold the messages right after `after` when it is
+    // given, and the newest ones below `before` otherwise
+    async fn serve(listener: TcpListener, minutes: i64, queries: Arc<Mutex<Vec<String>>>) {
+        let ids: Vec<Snowflake> = (0..minutes).map(id).collect();
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 8192];
+            let n = socket.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+            let query = request
+                .split(' ')
+                .nth(1)
+                .unwrap()
+                .split('?')
+                .nth(1)
+                .unwrap_or("");
+            queries.lock().unwrap().push(query.to_string());
+
+            let (mut before, mut after, mut limit) = (Snowflake::MAX, None, 50);
+            for (key, value) in query.split('&').filter_map(|kv| kv.split_once('=')) {
+                match key {
+                    "before" => before = value.parse().unwrap(),
+                    "after" => after = Some(value.parse().unwrap()),
+                    "limit" => limit = value.parse().unwrap(),
+                    _ => {}
+                }
+            }
+            let below = ids.iter().copied().filter(|&id| id < before);
+            let mut page: Vec<Snowflake> = match after {
+                Some(after) => below.filter(|&id| id > after).take(limit).collect(),
+                None => below.rev().take(limit).collect(),
+            };
+            page.sort_by(|a, b| b.cmp(a));
+
+            let messages: Vec<_> = page
+                .into_iter()
+                .map(|id| {
+                    json!({
+                        "id": id.to_string(),
+                        "channel_id": "7",
+                        "author": { "id": "1", "username": "alice" },
+                        "content": "",
+                        "timestamp": DateTime::<Utc>::from_utc(snowflakes::snowflake_timestamp(id), Utc),
+                        "tts": false,
+                        "mention_everyone": false,
+                        "pinned": false,
+                        "type": 0,
+                    })
+                })
+                .collect();
+            let body = json!(messages).to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    }
+
+    async fn mock(minutes: i64) -> (Http, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let queries = Arc::new(Mutex::new(Vec::new()));
+        tokio::spawn(serve(listener, minutes, queries.clone()));
+        (Http::with_base_url("token", base), queries)
+    }
+
+    async fn pages(http: &Http, range: HistoryRange) -> Vec<Vec<Snowflake>> {
+        http.message_history_pages(7, range)
+            .map(|page| page.unwrap().iter().map(|m| m.id).collect())
+            .collect()
+            .await
+    }
+
+    #[test]
+    fn bounds_are_exclusive_snowflakes() {
+        let range = HistoryRange::between(at(10), at(20));
+        assert_eq!(range.bounds(), (Some(id(10) - 1), Some(id(20))));
+        assert_eq!(HistoryRange::default().bounds(), (None, None));
+    }
+
+    #[test]
+    fn a_start_at_or_before_the_epoch_is_left_open() {
+        let epoch = midnight(2015, 1, 1);
+        for since in [epoch, epoch - Duration::days(1), midnight(1970, 1, 1)] {
+            let range = HistoryRange::between(since, at(0));
+            assert_eq!(range.bounds(), (None, Some(id(0))));
+        }
+
+        let range = HistoryRange::between(epoch + Duration::milliseconds(1), at(0));
+        assert_eq!(range.bounds(), (Some((1 << 22) - 1), Some(id(0))));
+    }
+
+    #[tokio::test]
+    async fn pages_run_newest_first_within_the_range() {
+        let (http, queries) = mock(250).await;
+
+        let pages = pages(&http, HistoryRange::between(at(50), at(200))).await;
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [100, 50]);
+        let ids: Vec<_> = pages.into_iter().flatten().collect();
+        assert_eq!(ids, (50..200).rev().map(id).collect::<Vec<_>>());
+        assert_eq!(
+            queries.lock().unwrap()[0],
+            format!("before={}&limit=100", id(200))
+        );
+    }
+
+    #[tokio::test]
+    async fn pages_run_oldest_first_within_the_range() {
+        let (http, _) = mock(250).await;
+
+        let range = HistoryRange::between(at(50), at(200)).oldest_first();
+        assert_eq!(range.direction, Direction::OldestFirst);
+        let pages = pages(&http, range).await;
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [100, 50]);
+        let ids: Vec<_> = pages.into_iter().flatten().collect();
+        assert_eq!(ids, (50..200).map(id).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn an_open_range_pages_through_the_whole_channel() {
+        let (http, queries) = mock(250).await;
+
+        let range = HistoryRange {
+            since: Some(midnight(1970, 1, 1)),
+            ..HistoryRange::default()
+        }
+        .oldest_first();
+        let pages = pages(&http, range).await;
+        assert_eq!(
+            pages.iter().map(Vec::len).collect::<Vec<_>>(),
+            [100, 100, 50]
+        );
+        let ids: Vec<_> = pages.into_iter().flatten().collect();
+        assert_eq!(ids, (0..250).map(id).collect::<Vec<_>>());
+
+        let queries = queries.lock().unwrap();
+        assert_eq!(queries[0], "after=0&limit=100");
+        assert_eq!(queries.len(), 3);
+    }
+}
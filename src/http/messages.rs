@@ -0,0 +1,301 @@
+use reqwest::Method;
+use serde::Serialize;
+
+use super::{with_reason, Http};
+use crate::types::messages::embeds::{self, EmbedError};
+use crate::types::messages::{
+    AllowedMentions, Component, Embed, Message, MessageFlags, MessageReference,
+};
+use crate::types::snowflakes::{self, Snowflake};
+use crate::DiscordErr;
+
+pub(crate) const MAX_LIMIT: u8 = 100; // the most messages the api returns per request
+pub(crate) const MAX_BULK_DELETE: usize = 100; // the most messages one bulk delete accepts
+
+// a page of channel history; around, before and after are mutually exclusive
+#[derive(Clone, Default, Debug)]
+pub struct MessageQuery {
+    pub around: Option<Snowflake>, // messages around this id
+    pub before: Option<Snowflake>, // messages with an id lower than this one
+    pub after: Option<Snowflake>,  // messages with an id greater than this one
+    pub limit: Option<u8>,         // messages per request (1-100), defaults to 50
+}
+
+impl MessageQuery {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(around) = self.around {
+            params.push(("around", around.to_string()));
+        }
+        if let Some(before) = self.before {
+            params.push(("before", before.to_string()));
+        }
+        if let Some(after) = self.after {
+            params.push(("after", after.to_string()));
+        }
+        if let Some(limit) = self.limit {
+            params.push(("limit", limit.clamp(1, MAX_LIMIT).to_string()));
+        }
+        params
+    }
+}
+
+fn no_flags(flags: &MessageFlags) -> bool {
+    flags.bits() == 0
+}
+
+// a message for Http::create_message; it needs content, an embed or a sticker.
+// allowed_mentions is always sent, so content never pings @everyone or roles
+// unless explicitly allowed
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct CreateMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    tts: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    embeds: Vec<Embed>,
+    allowed_mentions: AllowedMentions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_reference: Option<MessageReference>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    components: Vec<Component>,
+    #[serde(with = "snowflakes::vec", skip_serializing_if = "Vec::is_empty")]
+    sticker_ids: Vec<Snowflake>,
+    #[serde(skip_serializing_if = "no_flags")]
+    flags: MessageFlags,
+}
+
+impl CreateMessage {
+    pub fn new() -> Self {
+        CreateMessage::default()
+    }
+
+    // up to 2000 characters
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    pub fn tts(mut self, tts: bool) -> Self {
+        self.tts = tts;
+        self
+    }
+
+    // up to 10 embeds
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    pub fn embeds(mut self, embeds: Vec<Embed>) -> Self {
+        self.embeds = embeds;
+        self
+    }
+
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = allowed_mentions;
+        self
+    }
+
+    // replies to a message in the same channel; fails if it no longer exists
+    pub fn reply(self, message_id: Snowflake) -> Self {
+        self.message_reference(MessageReference {
+            message_id: Some(message_id),
+            ..MessageReference::default()
+        })
+    }
+
+    pub fn message_reference(mut self, reference: MessageReference) -> Self {
+        self.message_reference = Some(reference);
+        self
+    }
+
+    // action rows of buttons and select menus
+    pub fn components(mut self, components: Vec<Component>) -> Self {
+        self.components = components;
+        self
+    }
+
+    // up to 3 stickers
+    pub fn sticker_ids(mut self, ids: Vec<Snowflake>) -> Self {
+        self.sticker_ids = ids;
+        self
+    }
+
+    pub fn suppress_embeds(mut self, suppress: bool) -> Self {
+        set_flag(&mut self.flags, MessageFlags::SUPPRESS_EMBEDS, suppress);
+        self
+    }
+
+    // sends without push and desktop notifications, like the client's @silent
+    pub fn silent(mut self, silent: bool) -> Self {
+        set_flag(
+            &mut self.flags,
+            MessageFlags::SUPPRESS_NOTIFICATIONS,
+            silent,
+        );
+        self
+    }
+
+    // checks the embeds against discord's limits; sending does this too
+    pub fn validate(&self) -> Result<(), EmbedError> {
+        embeds::validate(&self.embeds)
+    }
+}
+
+// changes to a message for Http::edit_message; only the fields that were set are
+// sent, except allowed_mentions which always is
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct EditMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embeds: Option<Vec<Embed>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flags: Option<MessageFlags>,
+    allowed_mentions: AllowedMentions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    components: Option<Vec<Component>>,
+}
+
+impl EditMessage {
+    pub fn new() -> Self {
+        EditMessage::default()
+    }
+
+    // None removes the content
+    pub fn content(mut self, content: Option<&str>) -> Self {
+        self.content = Some(content.map(String::from));
+        self
+    }
+
+    // replaces all embeds; an empty list removes them
+    pub fn embeds(mut self, embeds: Vec<Embed>) -> Self {
+        self.embeds = Some(embeds);
+        self
+    }
+
+    // SUPPRESS_EMBEDS is the only flag that can be changed on an existing message
+    pub fn suppress_embeds(mut self, suppress: bool) -> Self {
+        let mut flags = self.flags.unwrap_or_default();
+        set_flag(&mut flags, MessageFlags::SUPPRESS_EMBEDS, suppress);
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = allowed_mentions;
+        self
+    }
+
+    pub fn components(mut self, components: Vec<Component>) -> Self {
+        self.components = Some(components);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), EmbedError> {
+        embeds::validate(self.embeds.as_deref().unwrap_or_default())
+    }
+}
+
+fn set_flag(flags: &mut MessageFlags, flag: MessageFlags, on: bool) {
+    if on {
+        flags.insert(flag);
+    } else {
+        flags.remove(flag);
+    }
+}
+
+impl Http {
+    // messages come back newest first
+    pub async fn get_channel_messages(
+        &self,
+        channel_id: Snowflake,
+        query: &MessageQuery,
+    ) -> Result<Vec<Message>, DiscordErr> {
+        let req = self
+            .request(Method::GET, &format!("/channels/{}/messages", channel_id))
+            .query(&query.params());
+        self.json(req).await
+    }
+
+    pub async fn get_message(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+    ) -> Result<Message, DiscordErr> {
+        let path = format!("/channels/{}/messages/{}", channel_id, message_id);
+        self.json(self.request(Method::GET, &path)).await
+    }
+
+    pub async fn create_message(
+        &self,
+        channel_id: Snowflake,
+        message: &CreateMessage,
+    ) -> Result<Message, DiscordErr> {
+        message.validate()?;
+        let req = self
+            .request(Method::POST, &format!("/channels/{}/messages", channel_id))
+            .json(message);
+        self.json(req).await
+    }
+
+    pub async fn edit_message(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        edit: &EditMessage,
+    ) -> Result<Message, DiscordErr> {
+        edit.validate()?;
+        let path = format!("/channels/{}/messages/{}", channel_id, message_id);
+        let req = self.request(Method::PATCH, &path).json(edit);
+        self.json(req).await
+    }
+
+    // the reason is only logged when deleting someone else's message
+    pub async fn delete_message(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("/channels/{}/messages/{}", channel_id, message_id);
+        let req = self.request(Method::DELETE, &path);
+        self.empty(with_reason(req, reason)).await
+    }
+
+    // deletes 2-100 messages at once; discord rejects the whole request if any
+    // of them is older than two weeks
+    pub async fn bulk_delete_messages(
+        &self,
+        channel_id: Snowflake,
+        message_ids: &[Snowflake],
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        #[derive(Serialize)]
+        struct BulkDelete<'a> {
+            #[serde(with = "snowflakes::vec")]
+            messages: &'a [Snowflake],
+        }
+
+        let path = format!("/channels/{}/messages/bulk-delete", channel_id);
+        let req = self.request(Method::POST, &path).json(&BulkDelete {
+            messages: message_ids,
+        });
+        self.empty(with_reason(req, reason)).await
+    }
+
+    // publishes a message in an announcement channel to the channels following it
+    pub async fn crosspost_message(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+    ) -> Result<Message, DiscordErr> {
+        let path = format!("/channels/{}/messages/{}/crosspost", channel_id, message_id);
+        let req = self
+            .request(Method::POST, &path)
+            .header("Content-Length", 0);
+        self.json(req).await
+    }
+}
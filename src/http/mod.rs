@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serializer};
+
+use crate::types::snowflakes::{self, Snowflake};
+use crate::DiscordErr;
+
+pub mod attachments;
+pub mod audit_logs;
+pub mod channels;
+pub mod guilds;
+pub mod history;
+pub mod messages;
+pub mod purge;
+pub mod reactions;
+pub mod roles;
+
+pub use attachments::AttachmentFile;
+pub use audit_logs::{AuditLogQuery, AuditLogWatcher, ExportFormat};
+pub use channels::{ChannelPosition, EditChannel, EditForumTag};
+pub use guilds::EditGuild;
+pub use history::{Direction, HistoryRange};
+pub use messages::{CreateMessage, EditMessage, MessageQuery};
+pub use purge::{bulk_deletable, PurgeFilter, PurgeProgress};
+pub use roles::EditRole;
+
+pub const API_BASE: &str = "https://discord.com/api/v10";
+
+const MAX_RETRIES: u32 = 3; // attempts made after a 429 before giving up
+
+#[derive(Clone)]
+pub struct Http {
+    client: reqwest::Client,
+    token: String,
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    #[serde(default)]
+    code: i64,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RateLimit {
+    retry_after: f64,
+    #[serde(default)]
+    global: bool,
+}
+
+impl Http {
+    pub fn new(token: impl Into<String>) -> Self {
+        Http::with_base_url(token, API_BASE)
+    }
+
+    // for proxies and for tests against a local mock server
+    pub fn with_base_url(token: impl Into<String>, base_url: impl Into<String>) -> Self {
+        Http {
+            client: reqwest::Client::new(),
+            token: token.into(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub(crate) fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.base_url, path))
+            .header("Authorization", format!("Bot {}", self.token))
+    }
+
+    // sends the request, retrying on 429 while the request can be cloned
+    pub(crate) async fn send(&self, req: RequestBuilder) -> Result<reqwest::Response, DiscordErr> {
+        let mut attempt = 0;
+        let mut req = req;
+        loop {
+            let retry = req.try_clone();
+            let res = req.send().await?;
+            let status = res.status();
+            if status.is_success() {
+                return Ok(res);
+            }
+
+            let body = res.bytes().await?;
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                let limit: RateLimit = serde_json::from_slice(&body)?;
+                match retry {
+                    Some(next) if attempt < MAX_RETRIES => {
+                        attempt += 1;
+                        tokio::time::sleep(Duration::from_secs_f64(limit.retry_after)).await;
+                        req = next;
+                        continue;
+                    }
+                    _ => {
+                        return Err(DiscordErr::RateLimited {
+                            retry_after: limit.retry_after,
+                            global: limit.global,
+                        })
+                    }
+                }
+            }
+
+            let err: ApiError = serde_json::from_slice(&body).unwrap_or(ApiError {
+                code: 0,
+                message: String::from_utf8_lossy(&body).into_owned(),
+            });
+            return Err(DiscordErr::Api {
+                status: status.as_u16(),
+                code: err.code,
+                message: err.message,
+            });
+        }
+    }
+
+    // for endpoints that answer 204 No Content
+    pub(crate) async fn empty(&self, req: RequestBuilder) -> Result<(), DiscordErr> {
+        self.send(req).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn json<T: DeserializeOwned>(
+        &self,
+        req: RequestBuilder,
+    ) -> Result<T, DiscordErr> {
+        let body = self.send(req).await?.bytes().await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+// sets the reason shown in the guild's audit log, which discord expects url-encoded
+pub(crate) fn with_reason(req: RequestBuilder, reason: Option<&str>) -> RequestBuilder {
+    match reason {
+        Some(reason) => req.header(
+            "X-Audit-Log-Reason",
+            utf8_percent_encode(reason, NON_ALPHANUMERIC).to_string(),
+        ),
+        None => req,
+    }
+}
+
+// for Option<Option<Snowflake>> fields skipped when None; Some(None) is sent as
+// null to clear the field
+pub(crate) fn nullable_id<S: Serializer>(
+    id: &Option<Option<Snowflake>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    snowflakes::option::serialize(&id.flatten(), s)
+}
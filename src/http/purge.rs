@@ -0,0 +1,151 @@
+use chrono::{Duration, Utc};
+use futures::stream::{self, Stream};
+
+use super::messages::{MessageQuery, MAX_BULK_DELETE, MAX_LIMIT};
+use super::Http;
+use crate::types::messages::Message;
+use crate::types::snowflakes::{self, Snowflake};
+use crate::types::DiscordTimestamp;
+use crate::DiscordErr;
+
+const BULK_DELETE_DAYS: i64 = 14; // bulk delete rejects messages older than this
+const BULK_DELETE_MARGIN: i64 = 60; // seconds kept clear of the cutoff so a slow purge doesn't cross it
+
+// which messages Http::purge_messages deletes; every field that is set must match
+#[derive(Clone, Default, Debug)]
+pub struct PurgeFilter {
+    pub author_id: Option<Snowflake>,     // messages sent by this user
+    pub content: Option<String>,          // messages containing this text, ignoring case
+    pub bots_only: bool,                  // messages sent by bots
+    pub before: Option<DiscordTimestamp>, // messages sent before this time
+    pub after: Option<DiscordTimestamp>,  // messages sent after this time
+    pub limit: Option<u64>,               // the most messages to delete
+}
+
+impl PurgeFilter {
+    pub fn matches(&self, message: &Message) -> bool {
+        self.author_id.is_none_or(|id| message.author.id == id)
+            && (!self.bots_only || message.author.bot)
+            && self.content.as_ref().is_none_or(|content| {
+                message
+                    .content
+                    .to_lowercase()
+                    .contains(&content.to_lowercase())
+            })
+    }
+}
+
+// running totals, yielded after every page of history
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PurgeProgress {
+    pub scanned: u64,      // messages looked at
+    pub matched: u64,      // messages that matched the filter
+    pub bulk_deleted: u64, // messages removed through bulk delete
+    pub deleted: u64,      // messages removed one at a time, mostly those too old to bulk delete
+}
+
+impl PurgeProgress {
+    pub fn total_deleted(&self) -> u64 {
+        self.bulk_deleted + self.deleted
+    }
+}
+
+struct Purge {
+    filter: PurgeFilter,
+    before: Option<Snowflake>,
+    after: Option<Snowflake>,
+    progress: PurgeProgress,
+}
+
+// whether the message is still young enough for bulk delete
+pub fn bulk_deletable(message_id: Snowflake) -> bool {
+    let cutoff =
+        Utc::now() - Duration::days(BULK_DELETE_DAYS) + Duration::seconds(BULK_DELETE_MARGIN);
+    snowflakes::snowflake_timestamp(message_id) > cutoff.naive_utc()
+}
+
+impl Http {
+    // deletes matching messages from the newest down, a page of history at a
+    // time. recent messages go through bulk delete in batches of up to 100,
+    // older ones are deleted individually. the stream ends after the last page
+    // or on the first error
+    pub fn purge_messages<'a>(
+        &'a self,
+        channel_id: Snowflake,
+        filter: PurgeFilter,
+        reason: Option<&'a str>,
+    ) -> impl Stream<Item = Result<PurgeProgress, DiscordErr>> + 'a {
+        let state = Purge {
+            before: filter.before.map(|t| snowflakes::new(t.naive_utc())),
+            after: filter.after.map(|t| snowflakes::new(t.naive_utc())),
+            filter,
+            progress: PurgeProgress::default(),
+        };
+
+        stream::try_unfold(Some(state), move |state| async move {
+            let mut state = match state {
+                Some(state) => state,
+                None => return Ok(None),
+            };
+            let limit = state.filter.limit;
+            if limit.is_some_and(|limit| state.progress.matched >= limit) {
+                return Ok(None);
+            }
+
+            let query = MessageQuery {
+                before: state.before,
+                limit: Some(MAX_LIMIT),
+                ..MessageQuery::default()
+            };
+            let page = self.get_channel_messages(channel_id, &query).await?;
+            let mut exhausted = page.len() < MAX_LIMIT as usize;
+            state.before = page.iter().map(|m| m.id).min();
+            if state.before.is_none() {
+                return Ok(None);
+            }
+
+            let mut bulk = Vec::new();
+            let mut single = Vec::new();
+            for message in &page {
+                if state.after.is_some_and(|after| message.id <= after) {
+                    exhausted = true;
+                    continue;
+                }
+                state.progress.scanned += 1;
+                if !state.filter.matches(message) {
+                    continue;
+                }
+                if limit.is_some_and(|limit| state.progress.matched >= limit) {
+                    exhausted = true;
+                    break;
+                }
+                state.progress.matched += 1;
+                if bulk_deletable(message.id) {
+                    bulk.push(message.id);
+                } else {
+                    single.push(message.id);
+                }
+            }
+
+            for batch in bulk.chunks(MAX_BULK_DELETE) {
+                match batch {
+                    [id] => {
+                        self.delete_message(channel_id, *id, reason).await?;
+                        state.progress.deleted += 1;
+                    }
+                    _ => {
+                        self.bulk_delete_messages(channel_id, batch, reason).await?;
+                        state.progress.bulk_deleted += batch.len() as u64;
+                    }
+                }
+            }
+            for id in single {
+                self.delete_message(channel_id, id, reason).await?;
+                state.progress.deleted += 1;
+            }
+
+            let progress = state.progress;
+            Ok(Some((progress, (!exhausted).then_some(state))))
+        })
+    }
+}
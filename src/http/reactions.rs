@@ -0,0 +1,184 @@
+use futures::stream::{self, Stream, TryStreamExt};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::Method;
+
+use super::Http;
+use crate::types::messages::ReactionType;
+use crate::types::snowflakes::Snowflake;
+use crate::types::users::User;
+use crate::DiscordErr;
+
+const MAX_LIMIT: u8 = 100; // the most users the api returns per request
+
+// the emoji as a path segment: the url-encoded unicode emoji, or name:id for
+// custom emojis, where discord only looks at the id
+fn encode_emoji(emoji: &ReactionType) -> String {
+    match emoji {
+        ReactionType::Unicode(emoji) => utf8_percent_encode(emoji, NON_ALPHANUMERIC).to_string(),
+        ReactionType::Custom { id, name, .. } => {
+            let name = name.as_deref().unwrap_or("_");
+            format!("{}:{}", utf8_percent_encode(name, NON_ALPHANUMERIC), id)
+        }
+    }
+}
+
+fn reactions_path(channel_id: Snowflake, message_id: Snowflake, emoji: &ReactionType) -> String {
+    format!(
+        "/channels/{}/messages/{}/reactions/{}",
+        channel_id,
+        message_id,
+        encode_emoji(emoji)
+    )
+}
+
+impl Http {
+    pub async fn create_reaction(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        emoji: &ReactionType,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("{}/@me", reactions_path(channel_id, message_id, emoji));
+        let req = self.request(Method::PUT, &path).header("Content-Length", 0);
+        self.empty(req).await
+    }
+
+    pub async fn delete_own_reaction(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        emoji: &ReactionType,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("{}/@me", reactions_path(channel_id, message_id, emoji));
+        self.empty(self.request(Method::DELETE, &path)).await
+    }
+
+    pub async fn delete_user_reaction(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        emoji: &ReactionType,
+        user_id: Snowflake,
+    ) -> Result<(), DiscordErr> {
+        let path = format!(
+            "{}/{}",
+            reactions_path(channel_id, message_id, emoji),
+            user_id
+        );
+        self.empty(self.request(Method::DELETE, &path)).await
+    }
+
+    pub async fn delete_all_reactions(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("/channels/{}/messages/{}/reactions", channel_id, message_id);
+        self.empty(self.request(Method::DELETE, &path)).await
+    }
+
+    pub async fn delete_all_reactions_for_emoji(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        emoji: &ReactionType,
+    ) -> Result<(), DiscordErr> {
+        let path = reactions_path(channel_id, message_id, emoji);
+        self.empty(self.request(Method::DELETE, &path)).await
+    }
+
+    // one page of users who reacted with the emoji, ordered by id; limit is
+    // 1-100 and defaults to 25
+    pub async fn get_reactions(
+        &self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        emoji: &ReactionType,
+        after: Option<Snowflake>,
+        limit: Option<u8>,
+    ) -> Result<Vec<User>, DiscordErr> {
+        let mut params = Vec::new();
+        if let Some(after) = after {
+            params.push(("after", after.to_string()));
+        }
+        if let Some(limit) = limit {
+            params.push(("limit", limit.clamp(1, MAX_LIMIT).to_string()));
+        }
+        let path = reactions_path(channel_id, message_id, emoji);
+        self.json(self.request(Method::GET, &path).query(&params))
+            .await
+    }
+
+    // pages of users who reacted with the emoji, fetched as the stream is polled
+    pub fn reaction_pages<'a>(
+        &'a self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        emoji: &'a ReactionType,
+    ) -> impl Stream<Item = Result<Vec<User>, DiscordErr>> + 'a {
+        stream::try_unfold(Some(None), move |after| async move {
+            let after = match after {
+                Some(after) => after,
+                None => return Ok(None),
+            };
+            let page = self
+                .get_reactions(channel_id, message_id, emoji, after, Some(MAX_LIMIT))
+                .await?;
+            let last = match page.iter().map(|u| u.id).max() {
+                Some(last) => last,
+                None => return Ok(None),
+            };
+            let next = (page.len() == MAX_LIMIT as usize).then_some(Some(last));
+            Ok(Some((page, next)))
+        })
+    }
+
+    // every user who reacted with the emoji
+    pub fn reactors<'a>(
+        &'a self,
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        emoji: &'a ReactionType,
+    ) -> impl Stream<Item = Result<User, DiscordErr>> + 'a {
+        self.reaction_pages(channel_id, message_id, emoji)
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_emoji, reactions_path};
+    use crate::types::messages::ReactionType;
+
+    #[test]
+    fn unicode_emojis_are_percent_encoded() {
+        assert_eq!(encode_emoji(&ReactionType::from('👍')), "%F0%9F%91%8D");
+        // a family: three emojis joined by zero width joiners
+        let family = ReactionType::from("👨\u{200d}👩\u{200d}👧");
+        assert_eq!(
+            encode_emoji(&family),
+            "%F0%9F%91%A8%E2%80%8D%F0%9F%91%A9%E2%80%8D%F0%9F%91%A7"
+        );
+        // a keycap: digit, variation selector and combining keycap
+        let keycap = ReactionType::from("1\u{fe0f}\u{20e3}");
+        assert_eq!(encode_emoji(&keycap), "1%EF%B8%8F%E2%83%A3");
+    }
+
+    #[test]
+    fn custom_emojis_are_sent_as_name_and_id() {
+        let blob = ReactionType::parse("<a:blob:123456>").unwrap();
+        assert_eq!(encode_emoji(&blob), "blob:123456");
+        assert_eq!(
+            reactions_path(1, 2, &blob),
+            "/channels/1/messages/2/reactions/blob:123456"
+        );
+
+        let unnamed = ReactionType::Custom {
+            id: 123456,
+            name: None,
+            animated: false,
+        };
+        assert_eq!(encode_emoji(&unnamed), "%5F:123456");
+    }
+}
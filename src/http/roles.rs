@@ -0,0 +1,181 @@
+use reqwest::Method;
+use serde::Serialize;
+
+use super::{with_reason, Http};
+use crate::types::cdn::{self, ImageFormat};
+use crate::types::guilds::roles::Role;
+use crate::types::permissions::Permissions;
+use crate::types::snowflakes::{self, Snowflake};
+use crate::DiscordErr;
+
+// a new role for Http::create_guild_role, or changes to one for
+// Http::modify_guild_role; only the fields that were set are sent
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct EditRole {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    permissions: Option<Permissions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hoist: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unicode_emoji: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mentionable: Option<bool>,
+    #[serde(skip)]
+    reason: Option<String>,
+}
+
+impl EditRole {
+    pub fn new() -> Self {
+        EditRole::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn permissions(mut self, permissions: impl Into<Permissions>) -> Self {
+        self.permissions = Some(permissions.into());
+        self
+    }
+
+    // rgb as 0xRRGGBB, 0 for no color
+    pub fn color(mut self, color: i32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn hoist(mut self, hoist: bool) -> Self {
+        self.hoist = Some(hoist);
+        self
+    }
+
+    // needs the ROLE_ICONS guild feature
+    pub fn icon(mut self, image: &[u8], format: ImageFormat) -> Self {
+        self.icon = Some(Some(cdn::data_uri(image, format)));
+        self
+    }
+
+    pub fn remove_icon(mut self) -> Self {
+        self.icon = Some(None);
+        self
+    }
+
+    pub fn unicode_emoji(mut self, emoji: Option<&str>) -> Self {
+        self.unicode_emoji = Some(emoji.map(String::from));
+        self
+    }
+
+    pub fn mentionable(mut self, mentionable: bool) -> Self {
+        self.mentionable = Some(mentionable);
+        self
+    }
+
+    // shown in the guild's audit log
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct RolePosition {
+    #[serde(with = "snowflakes::string")]
+    id: Snowflake,
+    position: i32,
+}
+
+impl Http {
+    pub async fn get_guild_roles(&self, guild_id: Snowflake) -> Result<Vec<Role>, DiscordErr> {
+        let req = self.request(Method::GET, &format!("/guilds/{}/roles", guild_id));
+        self.json(req).await
+    }
+
+    // unset fields get discord's defaults: "new role", no permissions beyond @everyone's
+    pub async fn create_guild_role(
+        &self,
+        guild_id: Snowflake,
+        role: &EditRole,
+    ) -> Result<Role, DiscordErr> {
+        let req = self
+            .request(Method::POST, &format!("/guilds/{}/roles", guild_id))
+            .json(role);
+        self.json(with_reason(req, role.reason.as_deref())).await
+    }
+
+    pub async fn modify_guild_role(
+        &self,
+        guild_id: Snowflake,
+        role_id: Snowflake,
+        edit: &EditRole,
+    ) -> Result<Role, DiscordErr> {
+        let req = self
+            .request(
+                Method::PATCH,
+                &format!("/guilds/{}/roles/{}", guild_id, role_id),
+            )
+            .json(edit);
+        self.json(with_reason(req, edit.reason.as_deref())).await
+    }
+
+    pub async fn delete_guild_role(
+        &self,
+        guild_id: Snowflake,
+        role_id: Snowflake,
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let req = self.request(
+            Method::DELETE,
+            &format!("/guilds/{}/roles/{}", guild_id, role_id),
+        );
+        self.empty(with_reason(req, reason)).await
+    }
+
+    // moves roles to new positions given as (role id, position); returns every role of the guild
+    pub async fn modify_guild_role_positions(
+        &self,
+        guild_id: Snowflake,
+        positions: &[(Snowflake, i32)],
+        reason: Option<&str>,
+    ) -> Result<Vec<Role>, DiscordErr> {
+        let body: Vec<RolePosition> = positions
+            .iter()
+            .map(|&(id, position)| RolePosition { id, position })
+            .collect();
+        let req = self
+            .request(Method::PATCH, &format!("/guilds/{}/roles", guild_id))
+            .json(&body);
+        self.json(with_reason(req, reason)).await
+    }
+
+    pub async fn add_guild_member_role(
+        &self,
+        guild_id: Snowflake,
+        user_id: Snowflake,
+        role_id: Snowflake,
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("/guilds/{}/members/{}/roles/{}", guild_id, user_id, role_id);
+        // discord rejects a PUT without a content length
+        let req = self.request(Method::PUT, &path).header("Content-Length", 0);
+        self.empty(with_reason(req, reason)).await
+    }
+
+    pub async fn remove_guild_member_role(
+        &self,
+        guild_id: Snowflake,
+        user_id: Snowflake,
+        role_id: Snowflake,
+        reason: Option<&str>,
+    ) -> Result<(), DiscordErr> {
+        let path = format!("/guilds/{}/members/{}/roles/{}", guild_id, user_id, role_id);
+        let req = self.request(Method::DELETE, &path);
+        self.empty(with_reason(req, reason)).await
+    }
+}
@@ -1,9 +1,80 @@
-mod types;
+pub mod types;
+#[allow(dead_code, unused_variables)] // still a stub
 mod gateway;
+pub mod http;
+
+use std::fmt;
 
 use types::users::User;
 
-pub struct DiscordErr;
+#[derive(Debug)]
+pub enum DiscordErr {
+    Http(reqwest::Error),      // the request could not be sent or its body could not be read
+    Decode(serde_json::Error), // the response was not the json we expected
+    Api {
+        status: u16,     // http status code
+        code: i64,       // discord's json error code, 0 when the body had none
+        message: String, // discord's error message
+    },
+    RateLimited {
+        retry_after: f64, // seconds to wait before retrying
+        global: bool,     // whether the global rate limit was hit
+    },
+    InvalidResponse, // the response was missing an expected field
+    Io(std::io::Error), // writing output such as an export failed
+    TooLarge {
+        size: u64,  // bytes in the upload or download, as far as it was read
+        limit: u64, // the most bytes that were allowed
+    },
+    InvalidEmbed(types::messages::embeds::EmbedError), // an embed broke a limit and was not sent
+}
+
+impl fmt::Display for DiscordErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiscordErr::Http(e) => write!(f, "http error: {}", e),
+            DiscordErr::Decode(e) => write!(f, "could not decode response: {}", e),
+            DiscordErr::Api { status, code, message } => {
+                write!(f, "discord returned {} (code {}): {}", status, code, message)
+            }
+            DiscordErr::RateLimited { retry_after, .. } => {
+                write!(f, "rate limited, retry after {}s", retry_after)
+            }
+            DiscordErr::InvalidResponse => f.write_str("invalid response"),
+            DiscordErr::Io(e) => write!(f, "io error: {}", e),
+            DiscordErr::TooLarge { size, limit } => {
+                write!(f, "{} bytes is over the limit of {} bytes", size, limit)
+            }
+            DiscordErr::InvalidEmbed(e) => write!(f, "invalid embed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DiscordErr {}
+
+impl From<reqwest::Error> for DiscordErr {
+    fn from(e: reqwest::Error) -> Self {
+        DiscordErr::Http(e)
+    }
+}
+
+impl From<std::io::Error> for DiscordErr {
+    fn from(e: std::io::Error) -> Self {
+        DiscordErr::Io(e)
+    }
+}
+
+impl From<types::messages::embeds::EmbedError> for DiscordErr {
+    fn from(e: types::messages::embeds::EmbedError) -> Self {
+        DiscordErr::InvalidEmbed(e)
+    }
+}
+
+impl From<serde_json::Error> for DiscordErr {
+    fn from(e: serde_json::Error) -> Self {
+        DiscordErr::Decode(e)
+    }
+}
 
 pub struct Bot {
     token: &'static str,
@@ -21,11 +92,8 @@ impl Bot {
     }
 
     pub async fn run(&self) {
-        let url = match gateway::resolve(self).await {
-            Ok(u) => u,
-            Err(_) => String::new()
-        };
+        let url = gateway::resolve(self).await.unwrap_or_default();
         gateway::connect(self, &url).await;
     }
 
-}
\ No newline at end of file
+}
@@ -1,10 +1,22 @@
-use super::guilds::events::{PrivacyLevel, Status};
-use super::guilds::roles::Role;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+use super::channels::Channel;
+use super::guilds::events::{GuildScheduledEvent, PrivacyLevel, Status};
+use super::guilds::{
+    DefaultMessageNotificationLevel, ExplicitContentFilterLevel, Integration, MfaLevel, NsfwLevel,
+    SystemChannelFlags, VerificationLevel,
+};
 use super::messages::stickers::StickerFormat;
-use super::permissions::overwrites::ChannelOverwrite;
-use super::snowflakes::Snowflake;
+use super::permissions::overwrites::{ChannelOverwrite, OverwriteType};
+use super::permissions::Permissions;
+use super::snowflakes::{self, Snowflake};
+use super::users::User;
+use super::webhooks::Webhook;
+use super::DiscordTimestamp;
 
-pub enum AuditLogEvent {
+int_enum!(AuditLogEvent: u8 {
     GuildUpdate = 1,
     ChannelCreate = 10,
     ChannelUpdate = 11,
@@ -52,329 +64,1633 @@ pub enum AuditLogEvent {
     ThreadCreate = 110,
     ThreadUpdate = 111,
     ThreadDelete = 112,
+    ApplicationCommandPermissionUpdate = 121,
+    AutoModerationRuleCreate = 140,
+    AutoModerationRuleUpdate = 141,
+    AutoModerationRuleDelete = 142,
+    AutoModerationBlockMessage = 143,
+    AutoModerationFlagToChannel = 144,
+    AutoModerationUserCommunicationDisabled = 145,
+});
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+#[serde(untagged)]
+pub enum IntStr {
+    Int(i64),
+    Str(String),
+}
+
+impl fmt::Display for IntStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntStr::Int(i) => write!(f, "{}", i),
+            IntStr::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct AuditLogRole {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake,
+    pub name: String,
+}
+
+// values of a change, decoded from the raw json of new_value / old_value
+trait ChangeValue: Sized {
+    fn from_json(value: Value) -> Result<Self, serde_json::Error>;
+    fn to_json(&self) -> Value;
+}
+
+macro_rules! change_value {
+    ($($t:ty),*) => {
+        $(impl ChangeValue for $t {
+            fn from_json(value: Value) -> Result<Self, serde_json::Error> {
+                serde_json::from_value(value)
+            }
+
+            fn to_json(&self) -> Value {
+                serde_json::to_value(self).unwrap_or(Value::Null)
+            }
+        })*
+    };
+}
+
+change_value!(
+    String,
+    i32,
+    bool,
+    Permissions,
+    VerificationLevel,
+    DefaultMessageNotificationLevel,
+    ExplicitContentFilterLevel,
+    MfaLevel,
+    NsfwLevel,
+    SystemChannelFlags,
+    Vec<ChannelOverwrite>,
+    Vec<AuditLogRole>,
+    StickerFormat,
+    PrivacyLevel,
+    Status,
+    IntStr,
+    DiscordTimestamp
+);
+
+impl ChangeValue for Snowflake {
+    fn from_json(value: Value) -> Result<Self, serde_json::Error> {
+        snowflakes::string::deserialize(value)
+    }
+
+    fn to_json(&self) -> Value {
+        Value::String(self.to_string())
+    }
 }
 
-union IntStr {
-    i: i32,
-    s: &'static str,
+fn decode<T: ChangeValue>(value: Option<Value>) -> Result<Option<T>, serde_json::Error> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => T::from_json(v).map(Some),
+    }
 }
 
-enum AuditLogChange {
-    AfkChannelId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * guild // afk channel changed
-    AfkTimeout {
-        new: i32,
-        old: i32,
-    }, // * guild // afk timeout duration changed
-    Allow {
-        new: &'static str,
-        old: &'static str,
-    }, // * role // a permission on a text or voice channel was allowed for a role
-    ApplicationId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * channel // application id of the added or removed webhook or bot
-    Archived {
-        new: bool,
-        old: bool,
-    }, // * thread // thread is now archived/unarchived
-    Asset {
-        new: &'static str,
-        old: &'static str,
-    }, // * sticker // empty &'static str
-    AutoArchiveDuration {
-        new: i32,
-        old: i32,
-    }, // * thread // auto archive duration changed
-    Available {
-        new: bool,
-        old: bool,
-    }, // * sticker // availability of sticker changed
-    AvatarHash {
-        new: &'static str,
-        old: &'static str,
-    }, // * user // user avatar changed
-    BannerHash {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild // guild banner changed
-    Bitrate {
-        new: i32,
-        old: i32,
-    }, // * channel // voice channel bitrate changed
-    ChannelId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * invite or guild scheduled event // channel for invite code or guild scheduled event changed
-    Code {
-        new: &'static str,
-        old: &'static str,
-    }, // * invite // invite code changed
-    Color {
-        new: i32,
-        old: i32,
-    }, // * role // role color changed
-    Deaf {
-        new: bool,
-        old: bool,
-    }, // * user // user server deafened/undeafened
-    DefaultAutoArchiveDuration {
-        new: i32,
-        old: i32,
-    }, // * channel // default auto archive duration for newly created threads changed
-    DefaultMessageNotifications {
-        new: i32,
-        old: i32,
-    }, // * guild // default message notification level changed
-    Deny {
-        new: &'static str,
-        old: &'static str,
-    }, // * role // a permission on a text or voice channel was denied for a role
-    Description {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild or sticker or guild scheduled event // description changed
-    DiscoverySplashHash {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild // discovery splash changed
-    EnableEmoticons {
-        new: bool,
-        old: bool,
-    }, // * integration // integration emoticons enabled/disabled
-    EntityType {
-        new: i32,
-        old: i32,
-    }, // * guild scheduled event // entity type of guild scheduled event was changed
-    ExpireBehavior {
-        new: i32,
-        old: i32,
-    }, // * integration // integration expiring subscriber behavior changed
-    ExpireGracePeriod {
-        new: i32,
-        old: i32,
-    }, // * integration // integration expire grace period changed
-    ExplicitContentFilter {
-        new: i32,
-        old: i32,
-    }, // * guild // change in whose messages are scanned and deleted for explicit content in the server
-    FormatType {
-        new: StickerFormat,
-        old: StickerFormat,
-    }, // * sticker // format type of sticker changed
-    GuildId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * sticker // guild sticker is in changed
-    Hoist {
-        new: bool,
-        old: bool,
-    }, // * role // role is now displayed/no longer displayed separate from online users
-    IconHash {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild or role // icon changed
-    Id {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * any // the id of the changed entity - sometimes used in conjunction with other keys
-    InviterId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * invite // person who created invite code changed
-    Location {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild scheduled event // change in channel id for guild scheduled event
-    Locked {
-        new: bool,
-        old: bool,
-    }, // * thread // thread is now locked/unlocked
-    MaxAge {
-        new: i32,
-        old: i32,
-    }, // * invite // how long invite code lasts changed
-    MaxUses {
-        new: i32,
-        old: i32,
-    }, // * invite // change to max number of times invite code can be used
-    Mentionable {
-        new: bool,
-        old: bool,
-    }, // * role // role is now mentionable/unmentionable
-    MfaLevel {
-        new: i32,
-        old: i32,
-    }, // * guild // two-factor auth requirement changed
-    Mute {
-        new: bool,
-        old: bool,
-    }, // * user // user server muted/unmuted
-    Name {
-        new: &'static str,
-        old: &'static str,
-    }, // * any // name changed
-    Nick {
-        new: &'static str,
-        old: &'static str,
-    }, // * user // user nickname changed
-    Nsfw {
-        new: bool,
-        old: bool,
-    }, // * channel // channel nsfw restriction changed
-    OwnerId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * guild // owner changed
-    PermissionOverwrites {
-        new: &'static [ChannelOverwrite],
-        old: &'static [ChannelOverwrite],
-    }, // * channel // permissions on a channel changed
-    Permissions {
-        new: &'static str,
-        old: &'static str,
-    }, // * role // permissions for a role changed
-    Position {
-        new: i32,
-        old: i32,
-    }, // * channel // text or voice channel position changed
-    PreferredLocale {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild // preferred locale changed
-    PrivacyLevel {
-        new: PrivacyLevel,
-        old: PrivacyLevel,
-    }, // * stage instance or guild scheduled event // privacy level of the stage instance changed
-    PruneDeleteDays {
-        new: i32,
-        old: i32,
-    }, // * guild // change in number of days after which inactive and role-unassigned members are kicked
-    PublicUpdatesChannelId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * guild // id of the public updates channel changed
-    RateLimitPerUser {
-        new: i32,
-        old: i32,
-    }, // * channel // amount of seconds a user has to wait before sending another message changed
-    Region {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild // region changed
-    RulesChannelId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * guild // id of the rules channel changed
-    SplashHash {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild // invite splash page artwork changed
-    Status {
-        new: Status,
-        old: Status,
-    }, // * guild scheduled event // status of guild scheduled event was changed
-    SystemChannelId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * guild // id of the system channel changed
-    Tags {
-        new: &'static str,
-        old: &'static str,
-    }, // * sticker // related emoji of sticker changed
-    Temporary {
-        new: bool,
-        old: bool,
-    }, // * invite // invite code is temporary/never expires
-    Topic {
-        new: &'static str,
-        old: &'static str,
-    }, // * channel or stage instance // text channel topic or stage instance topic changed
-    Type {
-        new: IntStr,
-        old: IntStr,
-    }, // * any // type of entity created
-    UnicodeEmoji {
-        new: &'static str,
-        old: &'static str,
-    }, // * role // role unicode emoji changed
-    UserLimit {
-        new: i32,
-        old: i32,
-    }, // * voice channel // new user limit in a voice channel
-    Uses {
-        new: i32,
-        old: i32,
-    }, // * invite // number of times invite code used changed
-    VanityUrlCode {
-        new: &'static str,
-        old: &'static str,
-    }, // * guild // guild invite vanity url changed
-    VerificationLevel {
-        new: i32,
-        old: i32,
-    }, // * guild // required verification level changed
-    WidgetChannelId {
-        new: Snowflake,
-        old: Snowflake,
-    }, // * guild // channel id of the server widget changed
-    WidgetEnabled {
-        new: bool,
-        old: bool,
-    }, // * guild // server widget enabled/disable
-    Add {
-        new: &'static [Role],
-        old: &'static [Role],
-    }, // * guild // new role added
-    Remove {
-        new: &'static [Role],
-        old: &'static [Role],
-    }, // * guild // role removed
+#[derive(Deserialize, Serialize)]
+struct RawChange {
+    key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_value: Option<Value>,
 }
 
-enum AuditEntryInfo {
+// each key of the api's {key, new_value, old_value} maps to a variant; either
+// value may be absent (e.g. on create or delete), and keys this crate does not
+// know yet are kept as raw json in Unknown. an explicit null is read as an absent
+// value, so it is left out when the change is serialized again
+macro_rules! audit_log_changes {
+    ($($variant:ident($key:literal): $t:ty,)*) => {
+        #[derive(Clone, PartialEq, Debug)]
+        pub enum AuditLogChange {
+            $($variant {
+                new: Option<$t>,
+                old: Option<$t>,
+            },)*
+            Unknown {
+                key: String,
+                new: Option<Value>,
+                old: Option<Value>,
+            },
+        }
+
+        impl AuditLogChange {
+            pub fn key(&self) -> &str {
+                match self {
+                    $(AuditLogChange::$variant { .. } => $key,)*
+                    AuditLogChange::Unknown { key, .. } => key,
+                }
+            }
+
+            // a known key whose values don't decode is kept as sent, like an unknown one
+            fn from_raw(raw: RawChange) -> Self {
+                let known = match raw.key.as_str() {
+                    $($key => match (decode(raw.new_value.clone()), decode(raw.old_value.clone())) {
+                        (Ok(new), Ok(old)) => Some(AuditLogChange::$variant { new, old }),
+                        _ => None,
+                    },)*
+                    _ => None,
+                };
+                known.unwrap_or(AuditLogChange::Unknown {
+                    key: raw.key,
+                    new: raw.new_value,
+                    old: raw.old_value,
+                })
+            }
+
+            fn to_raw(&self) -> RawChange {
+                match self {
+                    $(AuditLogChange::$variant { new, old } => RawChange {
+                        key: $key.to_string(),
+                        new_value: new.as_ref().map(ChangeValue::to_json),
+                        old_value: old.as_ref().map(ChangeValue::to_json),
+                    },)*
+                    AuditLogChange::Unknown { key, new, old } => RawChange {
+                        key: key.clone(),
+                        new_value: new.clone(),
+                        old_value: old.clone(),
+                    },
+                }
+            }
+        }
+    };
+}
+
+audit_log_changes! {
+    AfkChannelId("afk_channel_id"): Snowflake, // * guild // afk channel changed
+    AfkTimeout("afk_timeout"): i32, // * guild // afk timeout duration changed
+    Allow("allow"): Permissions, // * role // a permission on a text or voice channel was allowed for a role
+    ApplicationId("application_id"): Snowflake, // * channel // application id of the added or removed webhook or bot
+    Archived("archived"): bool, // * thread // thread is now archived/unarchived
+    Asset("asset"): String, // * sticker // empty string
+    AutoArchiveDuration("auto_archive_duration"): i32, // * thread // auto archive duration changed
+    Available("available"): bool, // * sticker // availability of sticker changed
+    AvatarHash("avatar_hash"): String, // * user // user avatar changed
+    BannerHash("banner_hash"): String, // * guild // guild banner changed
+    Bitrate("bitrate"): i32, // * channel // voice channel bitrate changed
+    ChannelId("channel_id"): Snowflake, // * invite or guild scheduled event // channel for invite code or guild scheduled event changed
+    Code("code"): String, // * invite // invite code changed
+    Color("color"): i32, // * role // role color changed
+    CommunicationDisabledUntil("communication_disabled_until"): DiscordTimestamp, // * member // member timed out or timeout removed
+    Deaf("deaf"): bool, // * user // user server deafened/undeafened
+    DefaultAutoArchiveDuration("default_auto_archive_duration"): i32, // * channel // default auto archive duration for newly created threads changed
+    DefaultMessageNotifications("default_message_notifications"): DefaultMessageNotificationLevel, // * guild // default message notification level changed
+    Deny("deny"): Permissions, // * role // a permission on a text or voice channel was denied for a role
+    Description("description"): String, // * guild or sticker or guild scheduled event // description changed
+    DiscoverySplashHash("discovery_splash_hash"): String, // * guild // discovery splash changed
+    EnableEmoticons("enable_emoticons"): bool, // * integration // integration emoticons enabled/disabled
+    EntityType("entity_type"): i32, // * guild scheduled event // entity type of guild scheduled event was changed
+    ExpireBehavior("expire_behavior"): i32, // * integration // integration expiring subscriber behavior changed
+    ExpireGracePeriod("expire_grace_period"): i32, // * integration // integration expire grace period changed
+    ExplicitContentFilter("explicit_content_filter"): ExplicitContentFilterLevel, // * guild // change in whose messages are scanned and deleted for explicit content in the server
+    FormatType("format_type"): StickerFormat, // * sticker // format type of sticker changed
+    GuildId("guild_id"): Snowflake, // * sticker // guild sticker is in changed
+    Hoist("hoist"): bool, // * role // role is now displayed/no longer displayed separate from online users
+    IconHash("icon_hash"): String, // * guild or role // icon changed
+    Id("id"): Snowflake, // * any // the id of the changed entity - sometimes used in conjunction with other keys
+    InviterId("inviter_id"): Snowflake, // * invite // person who created invite code changed
+    Location("location"): String, // * guild scheduled event // change in channel id for guild scheduled event
+    Locked("locked"): bool, // * thread // thread is now locked/unlocked
+    MaxAge("max_age"): i32, // * invite // how long invite code lasts changed
+    MaxUses("max_uses"): i32, // * invite // change to max number of times invite code can be used
+    Mentionable("mentionable"): bool, // * role // role is now mentionable/unmentionable
+    MfaLevel("mfa_level"): MfaLevel, // * guild // two-factor auth requirement changed
+    Mute("mute"): bool, // * user // user server muted/unmuted
+    Name("name"): String, // * any // name changed
+    Nick("nick"): String, // * user // user nickname changed
+    Nsfw("nsfw"): bool, // * channel // channel nsfw restriction changed
+    NsfwLevel("nsfw_level"): NsfwLevel, // * guild // guild nsfw level changed
+    OwnerId("owner_id"): Snowflake, // * guild // owner changed
+    PermissionOverwrites("permission_overwrites"): Vec<ChannelOverwrite>, // * channel // permissions on a channel changed
+    Permissions("permissions"): Permissions, // * role // permissions for a role changed
+    Position("position"): i32, // * channel // text or voice channel position changed
+    PreferredLocale("preferred_locale"): String, // * guild // preferred locale changed
+    PrivacyLevel("privacy_level"): PrivacyLevel, // * stage instance or guild scheduled event // privacy level of the stage instance changed
+    PruneDeleteDays("prune_delete_days"): i32, // * guild // change in number of days after which inactive and role-unassigned members are kicked
+    PublicUpdatesChannelId("public_updates_channel_id"): Snowflake, // * guild // id of the public updates channel changed
+    RateLimitPerUser("rate_limit_per_user"): i32, // * channel // amount of seconds a user has to wait before sending another message changed
+    Region("region"): String, // * guild // region changed
+    RulesChannelId("rules_channel_id"): Snowflake, // * guild // id of the rules channel changed
+    SplashHash("splash_hash"): String, // * guild // invite splash page artwork changed
+    Status("status"): Status, // * guild scheduled event // status of guild scheduled event was changed
+    SystemChannelFlags("system_channel_flags"): SystemChannelFlags, // * guild // system channel flags changed
+    SystemChannelId("system_channel_id"): Snowflake, // * guild // id of the system channel changed
+    Tags("tags"): String, // * sticker // related emoji of sticker changed
+    Temporary("temporary"): bool, // * invite // invite code is temporary/never expires
+    Topic("topic"): String, // * channel or stage instance // text channel topic or stage instance topic changed
+    Type("type"): IntStr, // * any // type of entity created
+    UnicodeEmoji("unicode_emoji"): String, // * role // role unicode emoji changed
+    UserLimit("user_limit"): i32, // * voice channel // new user limit in a voice channel
+    Uses("uses"): i32, // * invite // number of times invite code used changed
+    VanityUrlCode("vanity_url_code"): String, // * guild // guild invite vanity url changed
+    VerificationLevel("verification_level"): VerificationLevel, // * guild // required verification level changed
+    WidgetChannelId("widget_channel_id"): Snowflake, // * guild // channel id of the server widget changed
+    WidgetEnabled("widget_enabled"): bool, // * guild // server widget enabled/disable
+    Add("$add"): Vec<AuditLogRole>, // * guild // new role added
+    Remove("$remove"): Vec<AuditLogRole>, // * guild // role removed
+}
+
+impl<'de> Deserialize<'de> for AuditLogChange {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let raw = RawChange::deserialize(d)?;
+        Ok(AuditLogChange::from_raw(raw))
+    }
+}
+
+impl Serialize for AuditLogChange {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.to_raw().serialize(s)
+    }
+}
+
+// entry info decoded from AuditEntryOptions according to the entry's action type
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AuditEntryInfo {
+    ApplicationCommandPermission {
+        application_id: Snowflake, // id of the app whose permissions were targeted
+    },
+    AutoModeration {
+        rule_name: String, // name of the Auto Moderation rule that was triggered
+        trigger_type: u8,  // trigger type of the Auto Moderation rule that was triggered
+        channel_id: Option<Snowflake>, // channel in which the user's content was blocked or flagged
+    },
     ChannelOverwrite {
-        id: Snowflake,
-        role_name: &'static str,
-        entity_type: &'static str,
+        id: Snowflake,              // id of the overwritten entity
+        role_name: Option<String>,  // name of the role, not present for member overwrites
+        entity_type: OverwriteType, // type of overwritten entity
     },
     MemberDisconnect {
-        count: &'static str,
+        count: u64, // number of members disconnected
     },
     MemberMove {
-        channel_id: Snowflake,
-        count: &'static str,
+        channel_id: Snowflake, // channel the members were moved to
+        count: u64,            // number of members moved
     },
     MemberPrune {
-        delete_member_days: &'static str,
-        members_removed: &'static str,
+        delete_member_days: u64, // number of days after which inactive members were kicked
+        members_removed: u64,    // number of members removed by the prune
     },
     MessageBulkDelete {
-        count: &'static str,
+        count: u64, // number of messages deleted
     },
     MessageDelete {
-        channel_id: Snowflake,
-        count: &'static str,
+        channel_id: Snowflake, // channel the messages were deleted from
+        count: u64,            // number of messages deleted
     },
     MessagePinned {
-        channel_id: Snowflake,
-        message_id: Snowflake,
+        channel_id: Snowflake, // channel the message was pinned or unpinned in
+        message_id: Snowflake, // id of the message
     },
     StageInstance {
-        channel_id: Snowflake,
+        channel_id: Snowflake, // stage channel of the instance
     },
 }
 
-struct AuditLogEntry {
-    target_id: Snowflake,
-    changes: Option<&'static [AuditLogChange]>,
-    user_id: Option<Snowflake>,
-    id: Snowflake,
-    action_type: AuditLogEvent,
-    options: Option<AuditEntryInfo>,
-    reason: &'static str,
+// what an entry's target_id refers to, joined with the objects sent alongside the
+// entries where the api includes them
+#[derive(Clone, Copy, Debug)]
+pub enum AuditLogTarget<'a> {
+    Guild(Snowflake),
+    Channel(Snowflake),
+    Thread(Snowflake, Option<&'a Channel>),
+    User(Snowflake, Option<&'a User>),
+    Role(Snowflake),
+    Invite, // invites have no target id, the code is in the changes
+    Webhook(Snowflake, Option<&'a Webhook>),
+    Emoji(Snowflake),
+    Sticker(Snowflake),
+    Integration(Snowflake, Option<&'a Integration>),
+    StageInstance(Snowflake),
+    ScheduledEvent(Snowflake, Option<&'a GuildScheduledEvent>),
+    Command(Snowflake), // an application command, or the application itself when all of its commands were targeted
+    AutoModerationRule(Snowflake),
+}
+
+impl AuditLogTarget<'_> {
+    pub fn id(&self) -> Option<Snowflake> {
+        match *self {
+            AuditLogTarget::Invite => None,
+            AuditLogTarget::Guild(id)
+            | AuditLogTarget::Channel(id)
+            | AuditLogTarget::Thread(id, _)
+            | AuditLogTarget::User(id, _)
+            | AuditLogTarget::Role(id)
+            | AuditLogTarget::Webhook(id, _)
+            | AuditLogTarget::Emoji(id)
+            | AuditLogTarget::Sticker(id)
+            | AuditLogTarget::Integration(id, _)
+            | AuditLogTarget::StageInstance(id)
+            | AuditLogTarget::ScheduledEvent(id, _)
+            | AuditLogTarget::Command(id)
+            | AuditLogTarget::AutoModerationRule(id) => Some(id),
+        }
+    }
 }
 
-pub struct AuditLog {}
+// optional entry info as sent by the api; which fields are set depends on the action type
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
+pub struct AuditEntryOptions {
+    #[serde(
+        default,
+        with = "snowflakes::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub application_id: Option<Snowflake>, // id of the app whose permissions were targeted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_moderation_rule_name: Option<String>, // name of the Auto Moderation rule that was triggered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_moderation_rule_trigger_type: Option<String>, // trigger type of the Auto Moderation rule that was triggered
+    #[serde(
+        default,
+        with = "snowflakes::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub channel_id: Option<Snowflake>, // channel in which the entities were targeted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<String>, // number of entities that were targeted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete_member_days: Option<String>, // number of days after which inactive members were kicked
+    #[serde(
+        default,
+        with = "snowflakes::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub id: Option<Snowflake>, // id of the overwritten entity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members_removed: Option<String>, // number of members removed by the prune
+    #[serde(
+        default,
+        with = "snowflakes::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub message_id: Option<Snowflake>, // id of the message that was targeted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_name: Option<String>, // name of the role if type is "0" (not present if type is "1")
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub overwrite_type: Option<String>, // type of overwritten entity - role ("0") or member ("1")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integration_type: Option<String>, // the type of integration which performed the action
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct AuditLogEntry {
+    #[serde(default, with = "snowflakes::option")]
+    pub target_id: Option<Snowflake>,
+    pub changes: Option<Vec<AuditLogChange>>,
+    #[serde(default, with = "snowflakes::option")]
+    pub user_id: Option<Snowflake>,
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake,
+    pub action_type: AuditLogEvent,
+    pub options: Option<AuditEntryOptions>,
+    pub reason: Option<String>,
+}
+
+impl AuditLogEntry {
+    // decodes options for this entry's action type; None when the options are
+    // missing, incomplete or hold counts that are not numbers
+    pub fn info(&self) -> Option<AuditEntryInfo> {
+        use AuditLogEvent::*;
+
+        let o = self.options.as_ref()?;
+        let count = || o.count.as_deref()?.parse().ok();
+        let info = match self.action_type {
+            ApplicationCommandPermissionUpdate => AuditEntryInfo::ApplicationCommandPermission {
+                application_id: o.application_id?,
+            },
+            AutoModerationBlockMessage
+            | AutoModerationFlagToChannel
+            | AutoModerationUserCommunicationDisabled => AuditEntryInfo::AutoModeration {
+                rule_name: o.auto_moderation_rule_name.clone()?,
+                trigger_type: o
+                    .auto_moderation_rule_trigger_type
+                    .as_deref()?
+                    .parse()
+                    .ok()?,
+                channel_id: o.channel_id,
+            },
+            ChannelOverwriteCreate | ChannelOverwriteUpdate | ChannelOverwriteDelete => {
+                AuditEntryInfo::ChannelOverwrite {
+                    id: o.id?,
+                    role_name: o.role_name.clone(),
+                    entity_type: match o.overwrite_type.as_deref()? {
+                        "0" => OverwriteType::Role,
+                        "1" => OverwriteType::Member,
+                        _ => return None,
+                    },
+                }
+            }
+            MemberDisconnect => AuditEntryInfo::MemberDisconnect { count: count()? },
+            MemberMove => AuditEntryInfo::MemberMove {
+                channel_id: o.channel_id?,
+                count: count()?,
+            },
+            MemberPrune => AuditEntryInfo::MemberPrune {
+                delete_member_days: o.delete_member_days.as_deref()?.parse().ok()?,
+                members_removed: o.members_removed.as_deref()?.parse().ok()?,
+            },
+            MessageBulkDelete => AuditEntryInfo::MessageBulkDelete { count: count()? },
+            MessageDelete => AuditEntryInfo::MessageDelete {
+                channel_id: o.channel_id?,
+                count: count()?,
+            },
+            MessagePin | MessageUnpin => AuditEntryInfo::MessagePinned {
+                channel_id: o.channel_id?,
+                message_id: o.message_id?,
+            },
+            StageInstanceCreate | StageInstanceUpdate | StageInstanceDelete => {
+                AuditEntryInfo::StageInstance {
+                    channel_id: o.channel_id?,
+                }
+            }
+            _ => return None,
+        };
+        Some(info)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
+pub struct AuditLog {
+    #[serde(default)]
+    pub audit_log_entries: Vec<AuditLogEntry>, // list of audit log entries, sorted from most to least recent
+    #[serde(default)]
+    pub guild_scheduled_events: Vec<GuildScheduledEvent>, // list of guild scheduled events referenced in the audit log
+    #[serde(default)]
+    pub integrations: Vec<Integration>, // list of partial integration objects
+    #[serde(default)]
+    pub threads: Vec<Channel>, // list of threads referenced in the audit log
+    #[serde(default)]
+    pub users: Vec<User>, // list of users referenced in the audit log
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>, // list of webhooks referenced in the audit log
+}
+
+impl AuditLog {
+    pub fn user(&self, id: Snowflake) -> Option<&User> {
+        self.users.iter().find(|u| u.id == id)
+    }
+
+    pub fn webhook(&self, id: Snowflake) -> Option<&Webhook> {
+        self.webhooks.iter().find(|w| w.id == id)
+    }
+
+    pub fn integration(&self, id: Snowflake) -> Option<&Integration> {
+        self.integrations.iter().find(|i| i.id == id)
+    }
+
+    pub fn thread(&self, id: Snowflake) -> Option<&Channel> {
+        self.threads.iter().find(|t| t.id == id)
+    }
+
+    pub fn scheduled_event(&self, id: Snowflake) -> Option<&GuildScheduledEvent> {
+        self.guild_scheduled_events
+            .iter()
+            .find(|e| e.common().id == id)
+    }
+
+    // the user who made the changes
+    pub fn actor(&self, entry: &AuditLogEntry) -> Option<&User> {
+        self.user(entry.user_id?)
+    }
+
+    // the object the entry's action was performed on, interpreted by action type
+    pub fn target(&self, entry: &AuditLogEntry) -> Option<AuditLogTarget<'_>> {
+        use AuditLogEvent::*;
+
+        let id = match (entry.action_type, entry.target_id) {
+            (InviteCreate | InviteUpdate | InviteDelete, _) => return Some(AuditLogTarget::Invite),
+            (_, Some(id)) => id,
+            (_, None) => return None,
+        };
+        let target = match entry.action_type {
+            GuildUpdate => AuditLogTarget::Guild(id),
+            ChannelCreate
+            | ChannelUpdate
+            | ChannelDelete
+            | ChannelOverwriteCreate
+            | ChannelOverwriteUpdate
+            | ChannelOverwriteDelete
+            | MessageBulkDelete => AuditLogTarget::Channel(id),
+            MemberKick
+            | MemberPrune
+            | MemberBanAdd
+            | MemberBanRemove
+            | MemberUpdate
+            | MemberRoleUpdate
+            | MemberMove
+            | MemberDisconnect
+            | BotAdd
+            | MessageDelete
+            | MessagePin
+            | MessageUnpin
+            | AutoModerationBlockMessage
+            | AutoModerationFlagToChannel
+            | AutoModerationUserCommunicationDisabled => AuditLogTarget::User(id, self.user(id)),
+            RoleCreate | RoleUpdate | RoleDelete => AuditLogTarget::Role(id),
+            InviteCreate | InviteUpdate | InviteDelete => AuditLogTarget::Invite,
+            WebhookCreate | WebhookUpdate | WebhookDelete => {
+                AuditLogTarget::Webhook(id, self.webhook(id))
+            }
+            EmojiCreate | EmojiUpdate | EmojiDelete => AuditLogTarget::Emoji(id),
+            IntegrationCreate | IntegrationUpdate | IntegrationDelete => {
+                AuditLogTarget::Integration(id, self.integration(id))
+            }
+            StageInstanceCreate | StageInstanceUpdate | StageInstanceDelete => {
+                AuditLogTarget::StageInstance(id)
+            }
+            StickerCreate | StickerUpdate | StickerDelete => AuditLogTarget::Sticker(id),
+            GuildScheduledEventCreate | GuildScheduledEventUpdate | GuildScheduledEventDelete => {
+                AuditLogTarget::ScheduledEvent(id, self.scheduled_event(id))
+            }
+            ThreadCreate | ThreadUpdate | ThreadDelete => {
+                AuditLogTarget::Thread(id, self.thread(id))
+            }
+            ApplicationCommandPermissionUpdate => AuditLogTarget::Command(id),
+            AutoModerationRuleCreate | AutoModerationRuleUpdate | AutoModerationRuleDelete => {
+                AuditLogTarget::AutoModerationRule(id)
+            }
+            // the target of an action this crate does not know is unknown too
+            Unknown(_) => return None,
+        };
+        Some(target)
+    }
+
+    // the user whose member overwrite an overwrite entry changed
+    pub fn overwrite_user(&self, entry: &AuditLogEntry) -> Option<&User> {
+        match entry.info()? {
+            AuditEntryInfo::ChannelOverwrite {
+                id,
+                entity_type: OverwriteType::Member,
+                ..
+            } => self.user(id),
+            _ => None,
+        }
+    }
+}
+
+// turns entries into sentences such as "Alice changed the topic of #general from X to Y",
+// either as plain text, as discord markdown, or as a Sentence for localization
+pub mod render {
+    use std::collections::HashMap;
+    use std::fmt;
+
+    use serde_json::Value;
+
+    use super::{
+        AuditEntryInfo, AuditLog, AuditLogChange, AuditLogEntry, AuditLogEvent, AuditLogTarget,
+    };
+    use crate::types::guilds::{
+        DefaultMessageNotificationLevel, ExplicitContentFilterLevel, MfaLevel, NsfwLevel,
+        SystemChannelFlags, VerificationLevel,
+    };
+    use crate::types::permissions::overwrites::OverwriteType;
+    use crate::types::permissions::{Permission, Permissions};
+    use crate::types::snowflakes::Snowflake;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum Style {
+        Plain,    // names only, e.g. "Bob denied Send Messages for @Muted in #rules"
+        Markdown, // mentions and formatting for sending to discord
+    }
+
+    // a value substituted into a sentence template
+    #[derive(Clone, PartialEq, Debug)]
+    pub enum Arg {
+        Text(String), // wording such as "Someone" or the name of a field
+        User {
+            id: Snowflake,
+            name: Option<String>,
+        },
+        Channel {
+            id: Snowflake,
+            name: Option<String>,
+        },
+        Role {
+            id: Snowflake,
+            name: Option<String>,
+        },
+        Entity {
+            kind: &'static str, // e.g. "webhook" or "sticker"
+            name: Option<String>,
+            id: Option<Snowflake>,
+        },
+        Value(String), // a changed value
+        Permissions(Vec<Permission>),
+        Count(u64),
+        List(Vec<Arg>),
+    }
+
+    impl Arg {
+        pub fn render(&self, style: Style) -> String {
+            let md = style == Style::Markdown;
+            match self {
+                Arg::Text(text) => text.clone(),
+                Arg::User { id, .. } if md => format!("<@{}>", id),
+                Arg::User { id, name } => name.clone().unwrap_or_else(|| id.to_string()),
+                Arg::Channel { id, .. } if md => format!("<#{}>", id),
+                Arg::Channel { id, name } => match name {
+                    Some(name) => format!("#{}", name),
+                    None => format!("#{}", id),
+                },
+                Arg::Role { id, .. } if md => format!("<@&{}>", id),
+                Arg::Role { id, name } => match name {
+                    Some(name) => format!("@{}", name),
+                    None => format!("@{}", id),
+                },
+                Arg::Entity { kind, name, id } => {
+                    let name = match (name, id) {
+                        (Some(name), _) if md => Some(format!("**{}**", escape(name))),
+                        (Some(name), _) => Some(name.clone()),
+                        (None, Some(id)) => Some(id.to_string()),
+                        (None, None) => None,
+                    };
+                    match name {
+                        Some(name) => format!("the {} {}", kind, name),
+                        None => format!("the {}", kind),
+                    }
+                }
+                Arg::Value(value) if md => code(value),
+                Arg::Value(value) => value.clone(),
+                Arg::Permissions(permissions) => permissions
+                    .iter()
+                    .map(|p| match md {
+                        true => format!("**{}**", p.name()),
+                        false => p.name().to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Arg::Count(count) => count.to_string(),
+                Arg::List(args) => args
+                    .iter()
+                    .map(|arg| arg.render(style))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(c, '\\' | '*' | '_' | '~' | '`' | '|' | '>') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    fn code(text: &str) -> String {
+        match text.contains('`') {
+            true => format!("`` {} ``", text),
+            false => format!("`{}`", text),
+        }
+    }
+
+    // one line of output; key names the template for translators and args fill
+    // its {placeholders}
+    #[derive(Clone, PartialEq, Debug)]
+    pub struct Sentence {
+        pub key: &'static str,      // stable id of the template, e.g. "change.value"
+        pub template: &'static str, // the english template
+        pub args: Vec<(&'static str, Arg)>, // the value of each placeholder
+    }
+
+    impl Sentence {
+        fn new(key: &'static str, template: &'static str) -> Self {
+            Sentence {
+                key,
+                template,
+                args: Vec::new(),
+            }
+        }
+
+        fn with(mut self, name: &'static str, arg: Arg) -> Self {
+            self.args.push((name, arg));
+            self
+        }
+
+        pub fn arg(&self, name: &str) -> Option<&Arg> {
+            self.args
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, arg)| arg)
+        }
+
+        pub fn render(&self, style: Style) -> String {
+            let mut out = String::new();
+            let mut rest = self.template;
+            while let Some(start) = rest.find('{') {
+                let end = match rest[start..].find('}') {
+                    Some(end) => start + end,
+                    None => break,
+                };
+                out.push_str(&rest[..start]);
+                match self.arg(&rest[start + 1..end]) {
+                    Some(arg) => out.push_str(&arg.render(style)),
+                    None => out.push_str(&rest[start..=end]),
+                }
+                rest = &rest[end + 1..];
+            }
+            out.push_str(rest);
+            out
+        }
+    }
+
+    impl fmt::Display for Sentence {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(&self.render(Style::Plain))
+        }
+    }
+
+    // renders the entries of one audit log; channel and role names are not part of
+    // the response, so they are picked up from the entries where possible and can
+    // be filled in from the guild with channel() and role()
+    pub struct Renderer<'a> {
+        log: &'a AuditLog,
+        channels: HashMap<Snowflake, String>,
+        roles: HashMap<Snowflake, String>,
+    }
+
+    impl<'a> Renderer<'a> {
+        pub fn new(log: &'a AuditLog) -> Self {
+            let mut renderer = Renderer {
+                log,
+                channels: HashMap::new(),
+                roles: HashMap::new(),
+            };
+            for thread in &log.threads {
+                if let Some(name) = &thread.name {
+                    renderer.channels.insert(thread.id, name.clone());
+                }
+            }
+
+            // entries are sorted newest first, so the latest name is inserted last
+            for entry in log.audit_log_entries.iter().rev() {
+                match (log.target(entry), name_change(entry)) {
+                    (Some(AuditLogTarget::Role(id)), Some(name)) => {
+                        renderer.roles.insert(id, name);
+                    }
+                    (Some(AuditLogTarget::Channel(id)), Some(name))
+                    | (Some(AuditLogTarget::Thread(id, _)), Some(name)) => {
+                        renderer.channels.insert(id, name);
+                    }
+                    _ => {}
+                }
+                for change in entry.changes.iter().flatten() {
+                    if let AuditLogChange::Add {
+                        new: Some(roles), ..
+                    }
+                    | AuditLogChange::Remove {
+                        new: Some(roles), ..
+                    } = change
+                    {
+                        for role in roles {
+                            renderer.roles.insert(role.id, role.name.clone());
+                        }
+                    }
+                }
+                if let Some(AuditEntryInfo::ChannelOverwrite {
+                    id,
+                    role_name: Some(name),
+                    ..
+                }) = entry.info()
+                {
+                    renderer.roles.insert(id, name);
+                }
+            }
+            renderer
+        }
+
+        pub fn channel(mut self, id: Snowflake, name: impl Into<String>) -> Self {
+            self.channels.insert(id, name.into());
+            self
+        }
+
+        pub fn role(mut self, id: Snowflake, name: impl Into<String>) -> Self {
+            self.roles.insert(id, name.into());
+            self
+        }
+
+        // the entry as lines of text, one per sentence
+        pub fn render(&self, entry: &AuditLogEntry, style: Style) -> String {
+            self.sentences(entry)
+                .iter()
+                .map(|s| s.render(style))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        pub fn sentences(&self, entry: &AuditLogEntry) -> Vec<Sentence> {
+            use AuditLogEvent::*;
+
+            let actor = match entry.user_id {
+                Some(id) => self.user(id),
+                None => Arg::Text("Someone".to_string()),
+            };
+            let target = self.target(entry);
+            let about = |key, template| {
+                Sentence::new(key, template)
+                    .with("actor", actor.clone())
+                    .with("target", target.clone())
+            };
+
+            let sentence = match (entry.action_type, entry.info()) {
+                (
+                    ChannelOverwriteCreate | ChannelOverwriteUpdate | ChannelOverwriteDelete,
+                    info,
+                ) => return self.overwrite(entry, &actor, &target, info),
+                (MemberKick, _) => about("member.kick", "{actor} kicked {target}"),
+                (MemberBanAdd, _) => about("member.ban", "{actor} banned {target}"),
+                (MemberBanRemove, _) => about("member.unban", "{actor} unbanned {target}"),
+                (BotAdd, _) => about("bot.add", "{actor} added the bot {target}"),
+                (
+                    MemberPrune,
+                    Some(AuditEntryInfo::MemberPrune {
+                        delete_member_days,
+                        members_removed,
+                    }),
+                ) => about(
+                    "member.prune",
+                    "{actor} pruned {count} members inactive for {days} days",
+                )
+                .with("count", Arg::Count(members_removed))
+                .with("days", Arg::Count(delete_member_days)),
+                (MemberMove, Some(AuditEntryInfo::MemberMove { channel_id, count })) => {
+                    about("member.move", "{actor} moved {count} members to {channel}")
+                        .with("count", Arg::Count(count))
+                        .with("channel", self.channel_arg(channel_id))
+                }
+                (MemberDisconnect, Some(AuditEntryInfo::MemberDisconnect { count })) => about(
+                    "member.disconnect",
+                    "{actor} disconnected {count} members from voice",
+                )
+                .with("count", Arg::Count(count)),
+                (MessageDelete, Some(AuditEntryInfo::MessageDelete { channel_id, count })) => {
+                    about(
+                        "message.delete",
+                        "{actor} deleted {count} messages by {target} in {channel}",
+                    )
+                    .with("count", Arg::Count(count))
+                    .with("channel", self.channel_arg(channel_id))
+                }
+                (MessageBulkDelete, Some(AuditEntryInfo::MessageBulkDelete { count })) => about(
+                    "message.bulk_delete",
+                    "{actor} deleted {count} messages in {target}",
+                )
+                .with("count", Arg::Count(count)),
+                (MessagePin, Some(AuditEntryInfo::MessagePinned { channel_id, .. })) => about(
+                    "message.pin",
+                    "{actor} pinned a message by {target} in {channel}",
+                )
+                .with("channel", self.channel_arg(channel_id)),
+                (MessageUnpin, Some(AuditEntryInfo::MessagePinned { channel_id, .. })) => about(
+                    "message.unpin",
+                    "{actor} unpinned a message by {target} in {channel}",
+                )
+                .with("channel", self.channel_arg(channel_id)),
+                (action, Some(AuditEntryInfo::AutoModeration { rule_name, .. })) => {
+                    let (key, template) = match action {
+                        AutoModerationBlockMessage => (
+                            "automod.block",
+                            "Auto Moderation blocked a message by {target} ({rule})",
+                        ),
+                        AutoModerationFlagToChannel => (
+                            "automod.flag",
+                            "Auto Moderation flagged a message by {target} ({rule})",
+                        ),
+                        _ => (
+                            "automod.timeout",
+                            "Auto Moderation timed out {target} ({rule})",
+                        ),
+                    };
+                    about(key, template).with("rule", Arg::Value(rule_name))
+                }
+                (
+                    ChannelCreate
+                    | RoleCreate
+                    | InviteCreate
+                    | WebhookCreate
+                    | EmojiCreate
+                    | IntegrationCreate
+                    | StageInstanceCreate
+                    | StickerCreate
+                    | GuildScheduledEventCreate
+                    | ThreadCreate
+                    | AutoModerationRuleCreate,
+                    _,
+                ) => about("create", "{actor} created {target}"),
+                (
+                    ChannelDelete
+                    | RoleDelete
+                    | InviteDelete
+                    | WebhookDelete
+                    | EmojiDelete
+                    | IntegrationDelete
+                    | StageInstanceDelete
+                    | StickerDelete
+                    | GuildScheduledEventDelete
+                    | ThreadDelete
+                    | AutoModerationRuleDelete,
+                    _,
+                ) => about("delete", "{actor} deleted {target}"),
+                _ => {
+                    let sentences: Vec<Sentence> = entry
+                        .changes
+                        .iter()
+                        .flatten()
+                        .flat_map(|change| self.change(change, &actor, &target))
+                        .collect();
+                    if !sentences.is_empty() {
+                        return sentences;
+                    }
+                    about("update", "{actor} updated {target}")
+                }
+            };
+            vec![sentence]
+        }
+
+        fn user(&self, id: Snowflake) -> Arg {
+            Arg::User {
+                id,
+                name: self.log.user(id).map(|u| u.display_name().to_string()),
+            }
+        }
+
+        fn channel_arg(&self, id: Snowflake) -> Arg {
+            Arg::Channel {
+                id,
+                name: self.channels.get(&id).cloned(),
+            }
+        }
+
+        fn role_arg(&self, id: Snowflake) -> Arg {
+            Arg::Role {
+                id,
+                name: self.roles.get(&id).cloned(),
+            }
+        }
+
+        fn target(&self, entry: &AuditLogEntry) -> Arg {
+            let name = name_change(entry);
+            let entity = |kind, name, id| Arg::Entity { kind, name, id };
+            match self.log.target(entry) {
+                None => Arg::Text("something".to_string()),
+                Some(AuditLogTarget::Guild(_)) => Arg::Text("the server".to_string()),
+                Some(AuditLogTarget::Channel(id)) | Some(AuditLogTarget::Thread(id, _)) => {
+                    self.channel_arg(id)
+                }
+                Some(AuditLogTarget::User(id, _)) => self.user(id),
+                Some(AuditLogTarget::Role(id)) => self.role_arg(id),
+                Some(AuditLogTarget::Invite) => {
+                    let code = entry.changes.iter().flatten().find_map(|c| match c {
+                        AuditLogChange::Code { new, old } => new.clone().or_else(|| old.clone()),
+                        _ => None,
+                    });
+                    entity("invite", code, None)
+                }
+                Some(AuditLogTarget::Webhook(id, webhook)) => entity(
+                    "webhook",
+                    webhook.and_then(|w| w.name.clone()).or(name),
+                    Some(id),
+                ),
+                Some(AuditLogTarget::Emoji(id)) => entity("emoji", name, Some(id)),
+                Some(AuditLogTarget::Sticker(id)) => entity("sticker", name, Some(id)),
+                Some(AuditLogTarget::Integration(id, integration)) => entity(
+                    "integration",
+                    integration.map(|i| i.name.clone()).or(name),
+                    Some(id),
+                ),
+                Some(AuditLogTarget::StageInstance(id)) => entity("stage instance", None, Some(id)),
+                Some(AuditLogTarget::ScheduledEvent(id, event)) => entity(
+                    "event",
+                    event.map(|e| e.common().name.clone()).or(name),
+                    Some(id),
+                ),
+                Some(AuditLogTarget::Command(id)) => entity("command", None, Some(id)),
+                Some(AuditLogTarget::AutoModerationRule(id)) => {
+                    entity("auto moderation rule", name, Some(id))
+                }
+            }
+        }
+
+        fn change(&self, change: &AuditLogChange, actor: &Arg, target: &Arg) -> Vec<Sentence> {
+            let about = |key, template| {
+                Sentence::new(key, template)
+                    .with("actor", actor.clone())
+                    .with("target", target.clone())
+            };
+            let roles = |roles: &[super::AuditLogRole]| {
+                Arg::List(
+                    roles
+                        .iter()
+                        .map(|r| Arg::Role {
+                            id: r.id,
+                            name: Some(r.name.clone()),
+                        })
+                        .collect(),
+                )
+            };
+
+            match change {
+                AuditLogChange::Id { .. } | AuditLogChange::Type { .. } => Vec::new(),
+                AuditLogChange::Add { new: Some(new), .. } => {
+                    vec![about("member.roles.add", "{actor} gave {target} {roles}")
+                        .with("roles", roles(new))]
+                }
+                AuditLogChange::Remove { new: Some(new), .. } => {
+                    vec![about(
+                        "member.roles.remove",
+                        "{actor} removed {roles} from {target}",
+                    )
+                    .with("roles", roles(new))]
+                }
+                AuditLogChange::Permissions { new, old } => {
+                    let new = new.unwrap_or_default();
+                    let old = old.unwrap_or_default();
+                    let mut sentences = Vec::new();
+                    if !(new - old).is_empty() {
+                        sentences.push(
+                            about(
+                                "role.permissions.grant",
+                                "{actor} granted {permissions} to {target}",
+                            )
+                            .with("permissions", permissions(new - old)),
+                        );
+                    }
+                    if !(old - new).is_empty() {
+                        sentences.push(
+                            about(
+                                "role.permissions.revoke",
+                                "{actor} revoked {permissions} from {target}",
+                            )
+                            .with("permissions", permissions(old - new)),
+                        );
+                    }
+                    sentences
+                }
+                AuditLogChange::CommunicationDisabledUntil {
+                    new: Some(until), ..
+                } => {
+                    vec![
+                        about("member.timeout", "{actor} timed out {target} until {until}").with(
+                            "until",
+                            Arg::Value(until.format("%Y-%m-%d %H:%M UTC").to_string()),
+                        ),
+                    ]
+                }
+                AuditLogChange::CommunicationDisabledUntil { new: None, .. } => {
+                    vec![about(
+                        "member.timeout.remove",
+                        "{actor} removed the timeout of {target}",
+                    )]
+                }
+                _ => {
+                    let raw = change.to_raw();
+                    let field = Arg::Text(field_name(&raw.key));
+                    let new = raw.new_value.filter(|v| !v.is_null());
+                    let old = raw.old_value.filter(|v| !v.is_null());
+                    let opaque = raw.key.ends_with("_hash")
+                        || [&new, &old]
+                            .iter()
+                            .any(|v| matches!(v, Some(Value::Array(_) | Value::Object(_))));
+
+                    let sentence = match (new, old) {
+                        (None, None) => return Vec::new(),
+                        _ if opaque => about("change", "{actor} changed the {field} of {target}"),
+                        (Some(new), Some(old)) => about(
+                            "change.value",
+                            "{actor} changed the {field} of {target} from {old} to {new}",
+                        )
+                        .with("old", self.value(&raw.key, old))
+                        .with("new", self.value(&raw.key, new)),
+                        (Some(new), None) => {
+                            about("change.set", "{actor} set the {field} of {target} to {new}")
+                                .with("new", self.value(&raw.key, new))
+                        }
+                        (None, Some(old)) => about(
+                            "change.clear",
+                            "{actor} cleared the {field} of {target}, which was {old}",
+                        )
+                        .with("old", self.value(&raw.key, old)),
+                    };
+                    vec![sentence.with("field", field)]
+                }
+            }
+        }
+
+        fn overwrite(
+            &self,
+            entry: &AuditLogEntry,
+            actor: &Arg,
+            channel: &Arg,
+            info: Option<AuditEntryInfo>,
+        ) -> Vec<Sentence> {
+            let subject = match info {
+                Some(AuditEntryInfo::ChannelOverwrite {
+                    id,
+                    role_name,
+                    entity_type: OverwriteType::Role,
+                }) => Arg::Role {
+                    id,
+                    name: role_name.or_else(|| self.roles.get(&id).cloned()),
+                },
+                Some(AuditEntryInfo::ChannelOverwrite { id, .. }) => self.user(id),
+                _ => Arg::Text("someone".to_string()),
+            };
+            let about = |key, template| {
+                Sentence::new(key, template)
+                    .with("actor", actor.clone())
+                    .with("subject", subject.clone())
+                    .with("channel", channel.clone())
+            };
+
+            if entry.action_type == AuditLogEvent::ChannelOverwriteDelete {
+                return vec![about(
+                    "overwrite.delete",
+                    "{actor} removed the overwrite for {subject} in {channel}",
+                )];
+            }
+
+            let (mut allow, mut deny) = (
+                (Permissions::empty(), Permissions::empty()),
+                (Permissions::empty(), Permissions::empty()),
+            );
+            for change in entry.changes.iter().flatten() {
+                match change {
+                    AuditLogChange::Allow { new, old } => {
+                        allow = (new.unwrap_or_default(), old.unwrap_or_default())
+                    }
+                    AuditLogChange::Deny { new, old } => {
+                        deny = (new.unwrap_or_default(), old.unwrap_or_default())
+                    }
+                    _ => {}
+                }
+            }
+            let allowed = allow.0 - allow.1;
+            let denied = deny.0 - deny.1;
+            let reset = (allow.1 | deny.1) - (allow.0 | deny.0);
+
+            let mut sentences = Vec::new();
+            for (set, key, template) in [
+                (
+                    allowed,
+                    "overwrite.allow",
+                    "{actor} allowed {permissions} for {subject} in {channel}",
+                ),
+                (
+                    denied,
+                    "overwrite.deny",
+                    "{actor} denied {permissions} for {subject} in {channel}",
+                ),
+                (
+                    reset,
+                    "overwrite.reset",
+                    "{actor} reset {permissions} for {subject} in {channel}",
+                ),
+            ] {
+                if !set.is_empty() {
+                    sentences.push(about(key, template).with("permissions", permissions(set)));
+                }
+            }
+            if sentences.is_empty() {
+                sentences.push(match entry.action_type {
+                    AuditLogEvent::ChannelOverwriteCreate => about(
+                        "overwrite.create",
+                        "{actor} added an overwrite for {subject} in {channel}",
+                    ),
+                    _ => about(
+                        "overwrite.update",
+                        "{actor} updated the overwrite for {subject} in {channel}",
+                    ),
+                });
+            }
+            sentences
+        }
+
+        fn value(&self, key: &str, value: Value) -> Arg {
+            if let Some(name) = setting_name(key, &value) {
+                return Arg::Value(name);
+            }
+            let id = value.as_str().and_then(|s| s.parse::<Snowflake>().ok());
+            match (key, id, value) {
+                (key, Some(id), _) if key.ends_with("channel_id") => self.channel_arg(id),
+                ("owner_id" | "inviter_id", Some(id), _) => self.user(id),
+                ("permissions" | "allow" | "deny", Some(bits), _) => {
+                    permissions(Permissions(bits as u64))
+                }
+                (_, _, Value::String(s)) if s.is_empty() => Arg::Text("nothing".to_string()),
+                (_, _, Value::String(s)) => Arg::Value(s),
+                (_, _, Value::Bool(b)) => Arg::Value(if b { "on" } else { "off" }.to_string()),
+                (_, _, value) => Arg::Value(value.to_string()),
+            }
+        }
+    }
+
+    // guild settings by their names in the client rather than their numbers
+    fn setting_name(key: &str, value: &Value) -> Option<String> {
+        let n = value.as_u64()?;
+        let level = u8::try_from(n).ok();
+        Some(match key {
+            "verification_level" => VerificationLevel::from(level?).to_string(),
+            "default_message_notifications" => {
+                DefaultMessageNotificationLevel::from(level?).to_string()
+            }
+            "explicit_content_filter" => ExplicitContentFilterLevel::from(level?).to_string(),
+            "mfa_level" => MfaLevel::from(level?).to_string(),
+            "nsfw_level" => NsfwLevel::from(level?).to_string(),
+            "system_channel_flags" => SystemChannelFlags(n).to_string(),
+            _ => return None,
+        })
+    }
+
+    fn permissions(set: Permissions) -> Arg {
+        Arg::Permissions(set.iter().collect())
+    }
+
+    fn name_change(entry: &AuditLogEntry) -> Option<String> {
+        entry.changes.iter().flatten().find_map(|c| match c {
+            AuditLogChange::Name { new, old } => new.clone().or_else(|| old.clone()),
+            _ => None,
+        })
+    }
+
+    // "rate_limit_per_user" -> "slowmode", "afk_channel_id" -> "afk channel"
+    fn field_name(key: &str) -> String {
+        match key {
+            "rate_limit_per_user" => "slowmode".to_string(),
+            "nsfw" => "NSFW setting".to_string(),
+            "mfa_level" => "2FA requirement".to_string(),
+            key => key
+                .trim_end_matches("_hash")
+                .trim_end_matches("_id")
+                .replace('_', " "),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render::{Arg, Renderer, Style};
+    use super::{AuditEntryInfo, AuditLog, AuditLogChange, AuditLogTarget};
+    use crate::types::permissions::overwrites::OverwriteType;
+    use crate::types::permissions::Permission;
+    use serde_json::{json, Value};
+
+    // a log with alice (1) and bob (2) as the only known users
+    fn log(entries: Value) -> AuditLog {
+        serde_json::from_value(json!({
+            "audit_log_entries": entries,
+            "users": [
+                { "id": "1", "username": "alice", "global_name": "Alice" },
+                { "id": "2", "username": "bob", "global_name": "Bob" },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn explicit_nulls_are_read_as_absent_values() {
+        let raw = json!({ "key": "topic", "new_value": "rules", "old_value": null });
+        let change: AuditLogChange = serde_json::from_value(raw).unwrap();
+        assert_eq!(
+            change,
+            AuditLogChange::Topic {
+                new: Some("rules".to_string()),
+                old: None
+            }
+        );
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json, json!({ "key": "topic", "new_value": "rules" }));
+        assert_eq!(
+            serde_json::from_value::<AuditLogChange>(json).unwrap(),
+            change
+        );
+
+        let raw = json!({ "key": "shiny_new_field", "new_value": null, "old_value": 5 });
+        let change: AuditLogChange = serde_json::from_value(raw).unwrap();
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json, json!({ "key": "shiny_new_field", "old_value": 5 }));
+        assert_eq!(
+            serde_json::from_value::<AuditLogChange>(json).unwrap(),
+            change
+        );
+    }
+
+    #[test]
+    fn a_message_delete_targets_the_author_and_decodes_its_info() {
+        let log = log(json!([{
+            "id": "1100",
+            "user_id": "1",
+            "target_id": "2",
+            "action_type": 72,
+            "options": { "channel_id": "100", "count": "3" },
+            "changes": null,
+            "reason": null,
+        }]));
+        let entry = &log.audit_log_entries[0];
+
+        match log.target(entry) {
+            Some(AuditLogTarget::User(2, Some(user))) => assert_eq!(user.username, "bob"),
+            target => panic!("targets {:?}", target),
+        }
+        assert_eq!(
+            entry.info(),
+            Some(AuditEntryInfo::MessageDelete {
+                channel_id: 100,
+                count: 3
+            })
+        );
+    }
+
+    #[test]
+    fn a_member_not_in_users_is_still_targeted() {
+        let log = log(json!([{
+            "id": "1100",
+            "user_id": "1",
+            "target_id": "5",
+            "action_type": 20,
+            "reason": "spam",
+        }]));
+        let entry = &log.audit_log_entries[0];
+
+        assert!(matches!(
+            log.target(entry),
+            Some(AuditLogTarget::User(5, None))
+        ));
+        assert_eq!(entry.info(), None);
+    }
+
+    #[test]
+    fn a_role_update_targets_the_role() {
+        let log = log(json!([{
+            "id": "1100",
+            "user_id": "1",
+            "target_id": "300",
+            "action_type": 31,
+            "changes": [{ "key": "name", "old_value": "Muted", "new_value": "Quiet" }],
+        }]));
+        let entry = &log.audit_log_entries[0];
+
+        assert!(matches!(log.target(entry), Some(AuditLogTarget::Role(300))));
+        assert_eq!(log.target(entry).unwrap().id(), Some(300));
+        assert_eq!(entry.info(), None);
+    }
+
+    #[test]
+    fn an_overwrite_targets_the_channel_and_decodes_the_overwritten_entity() {
+        let log = log(json!([
+            {
+                "id": "1101",
+                "user_id": "1",
+                "target_id": "200",
+                "action_type": 13,
+                "options": { "id": "300", "type": "0", "role_name": "Muted" },
+            },
+            {
+                "id": "1100",
+                "user_id": "1",
+                "target_id": "200",
+                "action_type": 15,
+                "options": { "id": "2", "type": "1" },
+            },
+        ]));
+        let (role, member) = (&log.audit_log_entries[0], &log.audit_log_entries[1]);
+
+        assert!(matches!(
+            log.target(role),
+            Some(AuditLogTarget::Channel(200))
+        ));
+        assert_eq!(
+            role.info(),
+            Some(AuditEntryInfo::ChannelOverwrite {
+                id: 300,
+                role_name: Some("Muted".to_string()),
+                entity_type: OverwriteType::Role,
+            })
+        );
+        assert!(log.overwrite_user(role).is_none());
+
+        assert!(matches!(
+            log.target(member),
+            Some(AuditLogTarget::Channel(200))
+        ));
+        assert_eq!(
+            member.info(),
+            Some(AuditEntryInfo::ChannelOverwrite {
+                id: 2,
+                role_name: None,
+                entity_type: OverwriteType::Member,
+            })
+        );
+        assert_eq!(log.overwrite_user(member).unwrap().username, "bob");
+    }
+
+    #[test]
+    fn a_prune_has_no_target() {
+        let log = log(json!([
+            {
+                "id": "1101",
+                "user_id": "1",
+                "target_id": null,
+                "action_type": 21,
+                "options": { "delete_member_days": "7", "members_removed": "12" },
+            },
+            {
+                "id": "1100",
+                "user_id": "1",
+                "action_type": 21,
+                "options": { "delete_member_days": "7", "members_removed": "many" },
+            },
+        ]));
+        let (entry, broken) = (&log.audit_log_entries[0], &log.audit_log_entries[1]);
+
+        assert!(log.target(entry).is_none());
+        assert_eq!(
+            entry.info(),
+            Some(AuditEntryInfo::MemberPrune {
+                delete_member_days: 7,
+                members_removed: 12
+            })
+        );
+        assert!(log.target(broken).is_none());
+        assert_eq!(broken.info(), None);
+    }
+
+    #[test]
+    fn renders_a_changed_value() {
+        let log = log(json!([{
+            "id": "1000",
+            "user_id": "1",
+            "target_id": "100",
+            "action_type": 11,
+            "changes": [{ "key": "topic", "old_value": "X", "new_value": "Y" }],
+        }]));
+        let renderer = Renderer::new(&log).channel(100, "general");
+        let entry = &log.audit_log_entries[0];
+
+        assert_eq!(
+            renderer.render(entry, Style::Plain),
+            "Alice changed the topic of #general from X to Y"
+        );
+        assert_eq!(
+            renderer.render(entry, Style::Markdown),
+            "<@1> changed the topic of <#100> from `X` to `Y`"
+        );
+    }
+
+    #[test]
+    fn renders_an_overwrite_with_permission_names() {
+        let log = log(json!([{
+            "id": "1000",
+            "user_id": "2",
+            "target_id": "200",
+            "action_type": 14,
+            "options": { "id": "300", "type": "0", "role_name": "Muted" },
+            "changes": [
+                { "key": "allow", "old_value": "0", "new_value": "0" },
+                { "key": "deny", "old_value": "0", "new_value": "2048" },
+            ],
+        }]));
+        let renderer = Renderer::new(&log).channel(200, "rules");
+        let entry = &log.audit_log_entries[0];
+
+        assert_eq!(
+            renderer.render(entry, Style::Plain),
+            "Bob denied Send Messages for @Muted in #rules"
+        );
+        assert_eq!(
+            renderer.render(entry, Style::Markdown),
+            "<@2> denied **Send Messages** for <@&300> in <#200>"
+        );
+
+        // the structured form for localization
+        let sentences = renderer.sentences(entry);
+        assert_eq!(sentences.len(), 1);
+        assert_eq!(sentences[0].key, "overwrite.deny");
+        assert_eq!(
+            sentences[0].arg("permissions"),
+            Some(&Arg::Permissions(vec![Permission::SendMessages]))
+        );
+        assert_eq!(
+            sentences[0].arg("subject"),
+            Some(&Arg::Role {
+                id: 300,
+                name: Some("Muted".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_actions_fall_back_to_their_changes() {
+        let log = log(json!([
+            {
+                "id": "1001",
+                "user_id": "1",
+                "target_id": "100",
+                "action_type": 250,
+                "changes": [{ "key": "shiny_new_field", "old_value": "a", "new_value": "b" }],
+            },
+            {
+                "id": "1000",
+                "user_id": "1",
+                "target_id": "100",
+                "action_type": 250,
+            },
+        ]));
+        let renderer = Renderer::new(&log);
+
+        assert_eq!(
+            renderer.render(&log.audit_log_entries[0], Style::Plain),
+            "Alice changed the shiny new field of something from a to b"
+        );
+        assert_eq!(
+            renderer.render(&log.audit_log_entries[1], Style::Plain),
+            "Alice updated something"
+        );
+    }
+
+    #[test]
+    fn unknown_changes_are_described_by_their_key() {
+        let log = log(json!([{
+            "id": "1000",
+            "user_id": "1",
+            "target_id": "100",
+            "action_type": 11,
+            "changes": [
+                { "key": "shiny_new_field", "new_value": "on" },
+                { "key": "shiny_new_list", "old_value": [], "new_value": [1, 2] },
+                { "key": "shiny_new_name", "old_value": "old" },
+            ],
+        }]));
+        let renderer = Renderer::new(&log).channel(100, "general");
+
+        assert_eq!(
+            renderer.render(&log.audit_log_entries[0], Style::Plain),
+            "Alice set the shiny new field of #general to on\n\
+             Alice changed the shiny new list of #general\n\
+             Alice cleared the shiny new name of #general, which was old"
+        );
+    }
+
+    #[test]
+    fn unresolved_targets_fall_back_to_their_ids() {
+        let log = log(json!([
+            {
+                "id": "1002",
+                "target_id": "5",
+                "action_type": 20,
+            },
+            {
+                "id": "1001",
+                "user_id": "1",
+                "target_id": "100",
+                "action_type": 12,
+            },
+            {
+                "id": "1000",
+                "user_id": "1",
+                "action_type": 20,
+            },
+        ]));
+        let renderer = Renderer::new(&log);
+        let render = |i: usize, style| renderer.render(&log.audit_log_entries[i], style);
+
+        assert_eq!(render(0, Style::Plain), "Someone kicked 5");
+        assert_eq!(render(0, Style::Markdown), "Someone kicked <@5>");
+        assert_eq!(render(1, Style::Plain), "Alice deleted #100");
+        assert_eq!(render(2, Style::Plain), "Alice kicked something");
+    }
+
+    #[test]
+    fn change_with_an_unexpected_value_type_is_kept_as_unknown() {
+        let raw = json!({ "key": "color", "new_value": "red", "old_value": 5 });
+        let change: AuditLogChange = serde_json::from_value(raw.clone()).unwrap();
+        match &change {
+            AuditLogChange::Unknown { key, new, old } => {
+                assert_eq!(key, "color");
+                assert_eq!(new, &Some(json!("red")));
+                assert_eq!(old, &Some(json!(5)));
+            }
+            change => panic!("parsed as {:?}", change),
+        }
+        assert_eq!(serde_json::to_value(&change).unwrap(), raw);
+
+        let raw = json!({ "key": "color", "new_value": 7, "old_value": 5 });
+        let change: AuditLogChange = serde_json::from_value(raw).unwrap();
+        assert!(matches!(
+            change,
+            AuditLogChange::Color {
+                new: Some(7),
+                old: Some(5)
+            }
+        ));
+    }
+}
@@ -1,11 +1,141 @@
-pub enum Channel {
+use serde::{Deserialize, Serialize};
 
+use super::guilds::events::PrivacyLevel;
+use super::permissions::overwrites::ChannelOverwrite;
+use super::permissions::Permissions;
+use super::snowflakes::{self, Snowflake};
+use super::users::{Member, User};
+use super::DiscordTimestamp;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Channel {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // the id of this channel
+    #[serde(rename = "type")]
+    pub channel_type: ChannelType, // the type of channel
+    #[serde(default, with = "snowflakes::option")]
+    pub guild_id: Option<Snowflake>, // the id of the guild (may be missing for some channel objects received over gateway guild dispatches)
+    pub position: Option<i32>, // sorting position of the channel
+    #[serde(default)]
+    pub permission_overwrites: Vec<ChannelOverwrite>, // explicit permission overwrites for members and roles
+    pub name: Option<String>,  // the name of the channel (1-100 characters)
+    pub topic: Option<String>, // the channel topic (0-1024 characters)
+    pub nsfw: Option<bool>,    // whether the channel is nsfw
+    #[serde(default, with = "snowflakes::option")]
+    pub last_message_id: Option<Snowflake>, // the id of the last message sent in this channel (may not point to an existing or valid message)
+    pub bitrate: Option<i32>, // the bitrate (in bits) of the voice channel
+    pub user_limit: Option<i32>, // the user limit of the voice channel
+    pub rate_limit_per_user: Option<i32>, // amount of seconds a user has to wait before sending another message (0-21600)
+    pub recipients: Option<Vec<User>>,    // the recipients of the DM
+    pub icon: Option<String>,             // icon hash of the group DM
+    #[serde(default, with = "snowflakes::option")]
+    pub owner_id: Option<Snowflake>, // id of the creator of the group DM or thread
+    #[serde(default, with = "snowflakes::option")]
+    pub application_id: Option<Snowflake>, // application id of the group DM creator if it is bot-created
+    #[serde(default, with = "snowflakes::option")]
+    pub parent_id: Option<Snowflake>, // for guild channels: id of the parent category; for threads: id of the text channel this thread was created in
+    pub last_pin_timestamp: Option<DiscordTimestamp>, // when the last pinned message was pinned
+    pub rtc_region: Option<String>, // voice region id for the voice channel, automatic when set to null
+    pub video_quality_mode: Option<i32>, // the camera video quality mode of the voice channel, 1 when not present
+    pub message_count: Option<i32>, // an approximate count of messages in a thread, stops counting at 50
+    pub member_count: Option<i32>, // an approximate count of users in a thread, stops counting at 50
+    pub thread_metadata: Option<ThreadMetadata>, // thread-specific fields not needed by other channels
+    pub member: Option<ThreadMember>, // thread member object for the current user, if they have joined the thread
+    pub default_auto_archive_duration: Option<i32>, // default duration in minutes for newly created threads to stop showing in the channel list
+    pub permissions: Option<Permissions>, // computed permissions for the invoking user in the channel, including overwrites
+    pub flags: Option<i32>,               // channel flags combined as a bitfield
+    pub available_tags: Option<Vec<ForumTag>>, // the set of tags that can be used in a forum channel
+    #[serde(default, with = "snowflakes::vec")]
+    pub applied_tags: Vec<Snowflake>, // the ids of the set of tags that have been applied to a thread in a forum channel
+    pub default_reaction_emoji: Option<DefaultReaction>, // the emoji to show in the add reaction button on a thread in a forum channel
+    pub default_thread_rate_limit_per_user: Option<i32>, // the initial rate_limit_per_user to set on newly created threads
+}
+
+int_enum!(ChannelType: u8 {
+    GuildText = 0,           // a text channel within a server
+    Dm = 1,                  // a direct message between users
+    GuildVoice = 2,          // a voice channel within a server
+    GroupDm = 3,             // a direct message between multiple users
+    GuildCategory = 4,       // an organizational category that contains up to 50 channels
+    GuildAnnouncement = 5,   // a channel that users can follow and crosspost into their own server
+    AnnouncementThread = 10, // a temporary sub-channel within a GUILD_ANNOUNCEMENT channel
+    PublicThread = 11,       // a temporary sub-channel within a GUILD_TEXT or GUILD_FORUM channel
+    PrivateThread = 12, // a temporary sub-channel within a GUILD_TEXT channel that is only viewable by those invited
+    GuildStageVoice = 13, // a voice channel for hosting events with an audience
+    GuildDirectory = 14, // the channel in a hub containing the listed servers
+    GuildForum = 15,    // channel that can only contain threads
+    GuildMedia = 16,    // channel that can only contain threads, similar to GUILD_FORUM channels
+});
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ThreadMetadata {
+    pub archived: bool,                             // whether the thread is archived
+    pub auto_archive_duration: i32, // the thread will stop showing in the channel list after auto_archive_duration minutes of inactivity
+    pub archive_timestamp: DiscordTimestamp, // timestamp when the thread's archive status was last changed
+    pub locked: bool, // whether the thread is locked; when a thread is locked, only users with MANAGE_THREADS can unarchive it
+    pub invitable: Option<bool>, // whether non-moderators can add other non-moderators to a thread; only available on private threads
+    pub create_timestamp: Option<DiscordTimestamp>, // timestamp when the thread was created; only populated for threads created after 2022-01-09
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ThreadMember {
+    #[serde(default, with = "snowflakes::option")]
+    pub id: Option<Snowflake>, // the id of the thread
+    #[serde(default, with = "snowflakes::option")]
+    pub user_id: Option<Snowflake>, // the id of the user
+    pub join_timestamp: DiscordTimestamp, // the time the current user last joined the thread
+    pub flags: i32, // any user-thread settings, currently only used for notifications
 }
 
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ForumTag {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // the id of the tag
+    pub name: String,    // the name of the tag (0-20 characters)
+    pub moderated: bool, // whether this tag can only be added to or removed from threads by a member with the MANAGE_THREADS permission
+    #[serde(default, with = "snowflakes::option")]
+    pub emoji_id: Option<Snowflake>, // the id of a guild's custom emoji
+    pub emoji_name: Option<String>, // the unicode character of the emoji
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DefaultReaction {
+    #[serde(default, with = "snowflakes::option")]
+    pub emoji_id: Option<Snowflake>, // the id of a guild's custom emoji
+    pub emoji_name: Option<String>, // the unicode character of the emoji
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct VoiceState {
-    
+    #[serde(default, with = "snowflakes::option")]
+    pub guild_id: Option<Snowflake>, // the guild id this voice state is for
+    #[serde(default, with = "snowflakes::option")]
+    pub channel_id: Option<Snowflake>, // the channel id this user is connected to
+    #[serde(with = "snowflakes::string")]
+    pub user_id: Snowflake, // the user id this voice state is for
+    pub member: Option<Member>, // the guild member this voice state is for
+    pub session_id: String,     // the session id for this voice state
+    pub deaf: bool,             // whether this user is deafened by the server
+    pub mute: bool,             // whether this user is muted by the server
+    pub self_deaf: bool,        // whether this user is locally deafened
+    pub self_mute: bool,        // whether this user is locally muted
+    #[serde(default)]
+    pub self_stream: bool, // whether this user is streaming using "Go Live"
+    pub self_video: bool,  // whether this user's camera is enabled
+    pub suppress: bool,    // whether this user's permission to speak is denied
+    pub request_to_speak_timestamp: Option<DiscordTimestamp>, // the time at which the user requested to speak
 }
 
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct StageInstance {
-    
-}
\ No newline at end of file
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // the id of this Stage instance
+    #[serde(with = "snowflakes::string")]
+    pub guild_id: Snowflake, // the guild id of the associated Stage channel
+    #[serde(with = "snowflakes::string")]
+    pub channel_id: Snowflake, // the id of the associated Stage channel
+    pub topic: String,         // the topic of the Stage instance (1-120 characters)
+    pub privacy_level: PrivacyLevel, // the privacy level of the Stage instance
+    #[serde(default, with = "snowflakes::option")]
+    pub guild_scheduled_event_id: Option<Snowflake>, // the id of the scheduled event for this Stage instance
+}
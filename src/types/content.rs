@@ -0,0 +1,691 @@
+use std::fmt;
+use std::ops::Range;
+
+use chrono::{TimeZone, Utc};
+
+use super::messages::{Emoji, ReactionType};
+use super::snowflakes::Snowflake;
+use super::DiscordTimestamp;
+
+pub const MAX_CONTENT: usize = 2000; // characters in a message's content
+
+const MARKDOWN: &[char] = &[
+    '\\', '*', '_', '~', '`', '|', '>', '#', '-', '[', ']', '(', ')',
+];
+
+// backslash-escapes everything discord's markdown would act on, so the text
+// renders as typed
+pub fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if MARKDOWN.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// breaks up @everyone, @here and user, role and channel mentions with a zero
+// width space so they show as plain text and can't ping anyone, whatever the
+// allowed mentions say
+pub fn escape_mentions(text: &str) -> String {
+    text.replace("@everyone", "@\u{200b}everyone")
+        .replace("@here", "@\u{200b}here")
+        .replace("<@", "<@\u{200b}")
+        .replace("<#", "<#\u{200b}")
+}
+
+// for untrusted text such as user input: no markdown, no mentions
+pub fn escape(text: &str) -> String {
+    escape_mentions(&escape_markdown(text))
+}
+
+// splits content into pieces of at most `limit` characters, preferring to break
+// at newlines, then at whitespace. a code block cut in two is closed at the end
+// of one piece and reopened, with its language, at the start of the next
+pub fn split_content(text: &str, limit: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    let mut reopen: Option<String> = None;
+    loop {
+        let prefix = reopen.take().map(|fence| fence + "\n").unwrap_or_default();
+        let budget = limit.saturating_sub(prefix.chars().count());
+        if rest.chars().count() <= budget {
+            pieces.push(prefix + rest);
+            return pieces;
+        }
+
+        let inside = !prefix.is_empty();
+        let (mut head, mut tail) = fence_cut(rest, budget.max(1), inside);
+        if open_fence(&(prefix.clone() + head)).is_some() {
+            // cut again, leaving room to close the code block
+            (head, tail) = fence_cut(rest, budget.saturating_sub(4).max(1), inside);
+        }
+        let mut piece = prefix + head;
+        if let Some(fence) = open_fence(&piece) {
+            piece.push_str("\n```");
+            reopen = Some(fence);
+        }
+        pieces.push(piece);
+        rest = tail;
+    }
+}
+
+// the text up to at most max characters and what follows, dropping the newline
+// or space that was split at
+fn cut(text: &str, max: usize) -> (&str, &str) {
+    let end = text.char_indices().nth(max).map_or(text.len(), |(i, _)| i);
+    // whitespace right after the limit can be split at too
+    let next = text[end..].chars().next().filter(|c| c.is_whitespace());
+    let search = &text[..end + next.map_or(0, char::len_utf8)];
+    let at = search
+        .rfind('\n')
+        .or_else(|| search.rfind(char::is_whitespace))
+        .filter(|i| *i > 0);
+    match at {
+        Some(i) => {
+            let boundary = text[i..].chars().next().map_or(0, char::len_utf8);
+            (&text[..i], &text[i + boundary..])
+        }
+        None => (&text[..end], &text[end..]),
+    }
+}
+
+// like cut, but backs off to before a fence rather than splitting its backticks,
+// or ending a piece within or right after the opening line of a code block.
+// `inside` is whether the text starts within a code block
+fn fence_cut(text: &str, max: usize, mut inside: bool) -> (&str, &str) {
+    let (head, tail) = cut(text, max);
+    for (i, _) in text.match_indices("```") {
+        if i >= head.len() {
+            break;
+        }
+        let line = text[i..].find('\n').map_or(text.len(), |n| i + n);
+        let opening = !inside;
+        let splits = if inside {
+            head.len() < i + 3
+        } else {
+            head.len() <= line
+        };
+        inside = !inside;
+        if !splits {
+            continue;
+        }
+        let before = text[..i].strip_suffix('\n').unwrap_or(&text[..i]);
+        if !before.is_empty() {
+            return (before, &text[i..]);
+        }
+        // the text opens with the block, so keep its whole line and cut the code
+        let used = text[..line].chars().count() + 1;
+        if opening && line < text.len() && used < max {
+            let (code, rest) = fence_cut(&text[line + 1..], max - used, true);
+            return (&text[..line + 1 + code.len()], rest);
+        }
+    }
+    (head, tail)
+}
+
+// the opening line of a code block left open at the end of the text, if any
+fn open_fence(text: &str) -> Option<String> {
+    let mut open = None;
+    for (i, _) in text.match_indices("```") {
+        if open.take().is_none() {
+            let line = text[i..].lines().next().unwrap_or("```");
+            let lang = &line[3..];
+            let fence = if lang.contains('`') || lang.contains(char::is_whitespace) {
+                "```".to_string()
+            } else {
+                line.to_string()
+            };
+            open = Some(fence);
+        }
+    }
+    open
+}
+
+// puts zero width spaces into every run of backticks as long as `run`, however
+// long the run is, so the text can sit between delimiters of that length
+fn break_runs(text: &str, run: &str) -> String {
+    let broken = format!("`\u{200b}{}", &run[1..]);
+    let mut text = text.to_string();
+    while text.contains(run) {
+        text = text.replace(run, &broken);
+    }
+    text
+}
+
+// builds message content piece by piece. formatting helpers take their text as
+// is, so run untrusted text through escape or use push_safe
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct MessageBuilder {
+    content: String,
+}
+
+impl MessageBuilder {
+    pub fn new() -> Self {
+        MessageBuilder::default()
+    }
+
+    pub fn push(mut self, text: &str) -> Self {
+        self.content.push_str(text);
+        self
+    }
+
+    pub fn push_line(self, text: &str) -> Self {
+        self.push(text).push("\n")
+    }
+
+    // pushes untrusted text, escaping markdown and mentions
+    pub fn push_safe(self, text: &str) -> Self {
+        self.push(&escape(text))
+    }
+
+    pub fn mention_user(self, id: Snowflake) -> Self {
+        self.push(&format!("<@{}>", id))
+    }
+
+    pub fn mention_role(self, id: Snowflake) -> Self {
+        self.push(&format!("<@&{}>", id))
+    }
+
+    pub fn mention_channel(self, id: Snowflake) -> Self {
+        self.push(&format!("<#{}>", id))
+    }
+
+    pub fn emoji(self, emoji: &ReactionType) -> Self {
+        self.push(&emoji.to_string())
+    }
+
+    pub fn bold(self, text: &str) -> Self {
+        self.push(&format!("**{}**", text))
+    }
+
+    pub fn italic(self, text: &str) -> Self {
+        self.push(&format!("*{}*", text))
+    }
+
+    pub fn underline(self, text: &str) -> Self {
+        self.push(&format!("__{}__", text))
+    }
+
+    pub fn strikethrough(self, text: &str) -> Self {
+        self.push(&format!("~~{}~~", text))
+    }
+
+    pub fn spoiler(self, text: &str) -> Self {
+        self.push(&format!("||{}||", text))
+    }
+
+    // code can't be escaped, so backticks inside pick a longer delimiter and any
+    // run of them is broken up so it can't end the code
+    pub fn inline_code(self, code: &str) -> Self {
+        if code.contains('`') {
+            self.push(&format!("`` {} ``", break_runs(code, "``")))
+        } else {
+            self.push(&format!("`{}`", code))
+        }
+    }
+
+    // starts on a new line; runs of backticks inside the code are broken up so
+    // they can't end the block
+    pub fn code_block(self, language: Option<&str>, code: &str) -> Self {
+        let code = break_runs(code, "```");
+        let code = code.strip_suffix('\n').unwrap_or(&code);
+        self.start_line()
+            .push(&format!("```{}\n{}\n```", language.unwrap_or(""), code))
+    }
+
+    // quotes every line of the text
+    pub fn quote(self, text: &str) -> Self {
+        let quoted: Vec<String> = text.lines().map(|line| format!("> {}", line)).collect();
+        self.start_line().push(&quoted.join("\n"))
+    }
+
+    // level 1-3, on a line of its own
+    pub fn heading(self, level: u8, text: &str) -> Self {
+        let hashes = "#".repeat(level.clamp(1, 3) as usize);
+        self.start_line().push(&format!("{} {}", hashes, text))
+    }
+
+    // the angle brackets keep discord from adding a preview of the link; brackets
+    // in the text are escaped so they can't end it early
+    pub fn masked_link(self, text: &str, url: &str) -> Self {
+        let mut label = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(c, '\\' | '[' | ']') {
+                label.push('\\');
+            }
+            label.push(c);
+        }
+        self.push(&format!("[{}](<{}>)", label, url))
+    }
+
+    fn start_line(self) -> Self {
+        if self.content.is_empty() || self.content.ends_with('\n') {
+            self
+        } else {
+            self.push("\n")
+        }
+    }
+
+    // in characters, as discord counts them
+    pub fn len(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    // whether the content fits in one message
+    pub fn fits(&self) -> bool {
+        self.len() <= MAX_CONTENT
+    }
+
+    pub fn build(self) -> String {
+        self.content
+    }
+
+    // the content as one or more messages of at most 2000 characters
+    pub fn split(&self) -> Vec<String> {
+        split_content(&self.content, MAX_CONTENT)
+    }
+}
+
+impl fmt::Display for MessageBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.content)
+    }
+}
+
+// how a <t:..> timestamp is shown to each reader, in their own locale
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TimestampStyle {
+    ShortTime,     // t, 16:20
+    LongTime,      // T, 16:20:30
+    ShortDate,     // d, 20/04/2021
+    LongDate,      // D, 20 April 2021
+    ShortDateTime, // f, 20 April 2021 16:20 (the default)
+    LongDateTime,  // F, Tuesday, 20 April 2021 16:20
+    Relative,      // R, 2 months ago
+}
+
+impl TimestampStyle {
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            't' => TimestampStyle::ShortTime,
+            'T' => TimestampStyle::LongTime,
+            'd' => TimestampStyle::ShortDate,
+            'D' => TimestampStyle::LongDate,
+            'f' => TimestampStyle::ShortDateTime,
+            'F' => TimestampStyle::LongDateTime,
+            'R' => TimestampStyle::Relative,
+            _ => return None,
+        })
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            TimestampStyle::ShortTime => 't',
+            TimestampStyle::LongTime => 'T',
+            TimestampStyle::ShortDate => 'd',
+            TimestampStyle::LongDate => 'D',
+            TimestampStyle::ShortDateTime => 'f',
+            TimestampStyle::LongDateTime => 'F',
+            TimestampStyle::Relative => 'R',
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum TokenKind {
+    User(Snowflake),    // <@id> or the older nickname form <@!id>
+    Role(Snowflake),    // <@&id>
+    Channel(Snowflake), // <#id>
+    Command {
+        name: String, // the command, with any subcommand group and subcommand separated by spaces
+        id: Snowflake,
+    }, // </name:id>
+    Timestamp {
+        time: DiscordTimestamp,
+        style: Option<TimestampStyle>,
+    }, // <t:unix> or <t:unix:style>
+    Emoji(Box<Emoji>),  // <:name:id> or <a:name:id>, with id, name and animated set
+}
+
+// a mention or custom emoji found in message content
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub span: Range<usize>, // byte range in the text, angle brackets included
+    pub kind: TokenKind,
+}
+
+// every mention, command, timestamp and custom emoji in the text, in order;
+// anything that only looks like one, such as <@abc>, is skipped
+pub fn tokens(text: &str) -> impl Iterator<Item = Token> + '_ {
+    text.match_indices('<')
+        .filter_map(move |(start, _)| token_at(text, start))
+}
+
+// the token starting at the '<' at byte `start`, if it is one
+pub(crate) fn token_at(text: &str, start: usize) -> Option<Token> {
+    let len = text[start + 1..].find(['<', '>'])?;
+    let end = start + 1 + len;
+    if !text[end..].starts_with('>') {
+        return None;
+    }
+    Some(Token {
+        span: start..end + 1,
+        kind: parse_token(&text[start + 1..end])?,
+    })
+}
+
+fn parse_token(inner: &str) -> Option<TokenKind> {
+    if let Some(id) = inner.strip_prefix("@&") {
+        return Some(TokenKind::Role(parse_id(id)?));
+    }
+    if let Some(user) = inner.strip_prefix('@') {
+        let id = user.strip_prefix('!').unwrap_or(user);
+        return Some(TokenKind::User(parse_id(id)?));
+    }
+    if let Some(id) = inner.strip_prefix('#') {
+        return Some(TokenKind::Channel(parse_id(id)?));
+    }
+    if let Some(command) = inner.strip_prefix('/') {
+        let (name, id) = command.rsplit_once(':')?;
+        if name.is_empty() || name.trim() != name || name.contains(':') {
+            return None;
+        }
+        let name = name.to_string();
+        return Some(TokenKind::Command {
+            name,
+            id: parse_id(id)?,
+        });
+    }
+    if let Some(timestamp) = inner.strip_prefix("t:") {
+        let (unix, style) = match timestamp.split_once(':') {
+            Some((unix, style)) => {
+                let mut chars = style.chars();
+                let style = TimestampStyle::from_char(chars.next()?)?;
+                if chars.next().is_some() {
+                    return None;
+                }
+                (unix, Some(style))
+            }
+            None => (timestamp, None),
+        };
+        let unix = unix.strip_prefix('-').unwrap_or(unix);
+        let seconds: i64 = parse_digits(unix)?;
+        let seconds = if timestamp.starts_with('-') {
+            -seconds
+        } else {
+            seconds
+        };
+        let time = Utc.timestamp_opt(seconds, 0).single()?;
+        return Some(TokenKind::Timestamp { time, style });
+    }
+
+    let (animated, emoji) = match inner.strip_prefix("a:") {
+        Some(emoji) => (true, emoji),
+        None => (false, inner.strip_prefix(':')?),
+    };
+    let (name, id) = emoji.split_once(':')?;
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(TokenKind::Emoji(Box::new(Emoji {
+        id: Some(parse_id(id)?),
+        name: Some(name.to_string()),
+        roles: Vec::new(),
+        user: None,
+        require_colons: None,
+        managed: None,
+        animated: Some(animated),
+        available: None,
+    })))
+}
+
+fn parse_id(id: &str) -> Option<Snowflake> {
+    parse_digits(id)
+}
+
+// only plain digits, so "+1" or " 1" are rejected
+fn parse_digits(digits: &str) -> Option<i64> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::markdown::{parse, Node};
+
+    fn visible(code: &str) -> String {
+        code.replace('\u{200b}', "")
+    }
+
+    #[test]
+    fn masked_link_escapes_brackets_in_its_text() {
+        let content = MessageBuilder::new()
+            .masked_link("see [1] \\o/", "https://example.com")
+            .build();
+        assert_eq!(content, r"[see \[1\] \\o/](<https://example.com>)");
+        match parse(&content).as_slice() {
+            [Node::Link { url, children }] => {
+                assert_eq!(url, "https://example.com");
+                match children.as_slice() {
+                    [Node::Text(text)] => assert_eq!(text, r"see [1] \o/"),
+                    nodes => panic!("the text parsed as {:?}", nodes),
+                }
+            }
+            nodes => panic!("{:?} parsed as {:?}", content, nodes),
+        }
+    }
+
+    #[test]
+    fn code_block_keeps_any_run_of_backticks_inside() {
+        for code in ["a```b", "a````b", "a`````````b", "```", "a``b`"] {
+            let content = MessageBuilder::new()
+                .code_block(Some("rust"), code)
+                .push("after")
+                .build();
+            match parse(&content).as_slice() {
+                [Node::CodeBlock {
+                    language,
+                    code: got,
+                }, Node::Text(text)] => {
+                    assert_eq!(language.as_deref(), Some("rust"));
+                    assert_eq!(visible(got), code);
+                    assert_eq!(text, "after");
+                }
+                nodes => panic!("{:?} parsed as {:?}", code, nodes),
+            }
+        }
+    }
+
+    #[test]
+    fn inline_code_keeps_any_run_of_backticks_inside() {
+        for code in ["x`y", "x``y", "x```y", "x````y", "`x`"] {
+            let content = MessageBuilder::new().inline_code(code).push("!").build();
+            match parse(&content).as_slice() {
+                [Node::InlineCode(got), Node::Text(text)] => {
+                    assert_eq!(visible(got), code);
+                    assert_eq!(text, "!");
+                }
+                nodes => panic!("{:?} parsed as {:?}", code, nodes),
+            }
+        }
+    }
+
+    fn assert_fits(pieces: &[String], limit: usize) {
+        for piece in pieces {
+            assert!(
+                piece.chars().count() <= limit,
+                "{:?} is over {}",
+                piece,
+                limit
+            );
+        }
+    }
+
+    #[test]
+    fn split_content_keeps_text_that_fits_whole() {
+        let text = "a".repeat(MAX_CONTENT);
+        assert_eq!(split_content(&text, MAX_CONTENT), vec![text.clone()]);
+        assert_eq!(split_content("", 10), vec![String::new()]);
+
+        let pieces = split_content(&format!("{} b", text), MAX_CONTENT);
+        assert_eq!(pieces, vec![text, "b".to_string()]);
+    }
+
+    #[test]
+    fn split_content_prefers_newlines_then_spaces() {
+        assert_eq!(split_content("one two\nthree", 10), ["one two", "three"]);
+        assert_eq!(split_content("one two three", 10), ["one two", "three"]);
+        assert_eq!(split_content("abcdefgh", 3), ["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn split_content_counts_characters_not_bytes() {
+        let text = "é".repeat(5) + " " + &"日本".repeat(3);
+        let pieces = split_content(&text, 6);
+        assert_eq!(pieces, ["ééééé", "日本日本日本"]);
+
+        let pieces = split_content(&"🦀".repeat(7), 3);
+        assert_fits(&pieces, 3);
+        assert_eq!(pieces.concat(), "🦀".repeat(7));
+    }
+
+    #[test]
+    fn split_content_reopens_a_code_block_with_its_language() {
+        let code = (0..10)
+            .map(|i| format!("let a{} = {};", i, i))
+            .collect::<Vec<_>>();
+        let text = format!("```rust\n{}\n```", code.join("\n"));
+        let pieces = split_content(&text, 40);
+        assert_fits(&pieces, 40);
+        assert!(pieces.len() > 1);
+        for piece in &pieces {
+            assert!(piece.starts_with("```rust\n"), "{:?}", piece);
+            assert!(piece.ends_with("\n```"), "{:?}", piece);
+        }
+        let lines = pieces
+            .iter()
+            .flat_map(|piece| piece.lines())
+            .filter(|line| !line.starts_with("```"))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, code);
+    }
+
+    #[test]
+    fn split_content_never_cuts_through_a_fence() {
+        for (filler, sep) in (1..40).flat_map(|n| [(n, ""), (n, "\n")]) {
+            let text = format!("{}{}```rust\nlet a = 1;\n```", "y".repeat(filler), sep);
+            for limit in 20..text.len() {
+                let pieces = split_content(&text, limit);
+                assert_fits(&pieces, limit);
+                for piece in &pieces {
+                    assert_eq!(piece.matches("```").count() % 2, 0, "{:?}", pieces);
+                    for line in piece.lines().filter(|line| line.contains('`')) {
+                        assert!(line.ends_with("```rust") || line == "```", "{:?}", pieces);
+                    }
+                    assert!(!piece.contains("```rust\n```"), "{:?}", pieces);
+                }
+                if limit >= 24 {
+                    assert!(pieces.iter().any(|piece| piece.contains("let a = 1;")));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn token_spans_are_byte_offsets_after_multibyte_text() {
+        let text = "héllo 🦀 <@123> and <#456>";
+        let found: Vec<_> = tokens(text).collect();
+        assert_eq!(found.len(), 2);
+        assert_eq!(&text[found[0].span.clone()], "<@123>");
+        assert_eq!(found[0].span, 12..18);
+        assert!(matches!(found[0].kind, TokenKind::User(123)));
+        assert_eq!(&text[found[1].span.clone()], "<#456>");
+        assert!(matches!(found[1].kind, TokenKind::Channel(456)));
+    }
+
+    #[test]
+    fn mentions_commands_and_emoji() {
+        let kinds: Vec<_> = tokens("<@!1> <@&2> </ban user:3> <:ok:4> <a:wave_2:5>")
+            .map(|token| token.kind)
+            .collect();
+        assert!(matches!(kinds[0], TokenKind::User(1)));
+        assert!(matches!(kinds[1], TokenKind::Role(2)));
+        assert!(matches!(&kinds[2], TokenKind::Command { name, id: 3 } if name == "ban user"));
+        match (&kinds[3], &kinds[4]) {
+            (TokenKind::Emoji(ok), TokenKind::Emoji(wave)) => {
+                assert_eq!(
+                    (ok.id, ok.name.as_deref(), ok.animated),
+                    (Some(4), Some("ok"), Some(false))
+                );
+                assert_eq!(
+                    (wave.id, wave.name.as_deref(), wave.animated),
+                    (Some(5), Some("wave_2"), Some(true))
+                );
+            }
+            kinds => panic!("parsed as {:?}", kinds),
+        }
+        assert_eq!(kinds.len(), 5);
+    }
+
+    #[test]
+    fn timestamps_with_and_without_style() {
+        let kinds: Vec<_> = tokens("<t:1618953630> <t:1618953630:R> <t:-86400:d>")
+            .map(|token| token.kind)
+            .collect();
+        let expected = [
+            (1618953630, None),
+            (1618953630, Some(TimestampStyle::Relative)),
+            (-86400, Some(TimestampStyle::ShortDate)),
+        ];
+        assert_eq!(kinds.len(), expected.len());
+        for (kind, (seconds, expected_style)) in kinds.iter().zip(expected) {
+            match kind {
+                TokenKind::Timestamp { time, style } => {
+                    assert_eq!(time.timestamp(), seconds);
+                    assert_eq!(*style, expected_style);
+                }
+                kind => panic!("parsed as {:?}", kind),
+            }
+        }
+    }
+
+    #[test]
+    fn look_alikes_are_not_tokens() {
+        for text in [
+            "<@abc>",
+            "<@>",
+            "<@ 1>",
+            "<@+1>",
+            "<@!-1>",
+            "<#1 >",
+            "<@&>",
+            "<t:12:x>",
+            "<t:12:RR>",
+            "<t:--5>",
+            "<t:>",
+            "</:1>",
+            "</ ban:1>",
+            "<::1>",
+            "<:bad-name:1>",
+            "<:ok:>",
+            "<a:ok>",
+            "<@1",
+        ] {
+            assert_eq!(tokens(text).count(), 0, "{:?}", text);
+        }
+        // a stray '<' doesn't hide the token after it
+        let found: Vec<_> = tokens("<@<@1>").collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span, 2..6);
+    }
+}
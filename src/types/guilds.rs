@@ -1,175 +1,936 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
 use super::channels::{Channel, StageInstance, VoiceState};
 use super::messages::{Emoji, Sticker};
-use super::snowflakes::Snowflake;
-use super::users::{Member, PresenceUpdate};
+use super::permissions::Permissions;
+use super::snowflakes::{self, Snowflake};
+use super::users::{Member, PresenceUpdate, User};
+use super::DiscordTimestamp;
 
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Guild {
-    id: Snowflake,                                     // guild id
-    name: &'static str, // guild name (2-100 characters, excluding trailing and leading whitespace)
-    icon: &'static str, // icon hash
-    icon_hash: Option<&'static str>, //icon hash, returned when in the template object
-    splash: &'static str, // splash hash
-    discovery_splash: &'static str, // discovery splash hash; only present for guilds with the "DISCOVERABLE" feature
-    owner: Option<bool>,            // ** true if the user is the owner of the guild
-    owner_id: Snowflake,            // id of owner
-    permissions: Option<&'static str>, // ** total permissions for the user in the guild (excludes overwrites)
-    region: Option<&'static str>,      // *** voice region id for the guild (deprecated)
-    afk_channel_id: Snowflake,         // id of afk channel
-    afk_timeout: i32,                  // afk timeout in seconds
-    widget_enabled: Option<bool>,      //true if the server widget is enabled
-    widget_channel_id: Option<Snowflake>, //the channel id that the widget will generate an invite to, or null if set to no invite
-    verification_level: i32,              // verification level required for the guild
-    default_message_notifications: i32,   // default message notifications level
-    explicit_content_filter: i32,         // explicit content filter level
-    roles: &'static [roles::Role],        // roles in the guild
-    emojis: &'static [Emoji],             // custom guild emojis
-    features: &'static [GuildFeature],    // enabled guild features
-    mfa_level: i32,                       // required MFA level for the guild
-    application_id: Snowflake, // application id of the guild creator if it is bot-created
-    system_channel_id: Snowflake, // the id of the channel where guild notices such as welcome messages and boost events are posted
-    system_channel_flags: i32,    // system channel flags
-    rules_channel_id: Snowflake, // the id of the channel where Community guilds can display rules and/or guidelines
-    joined_at: Option<super::DiscordTimestamp>, // * when this guild was joined at
-    large: Option<bool>,         // * true if this is considered a large guild
-    unavailable: Option<bool>,   // * true if this guild is unavailable due to an outage
-    member_count: Option<i32>,   // * total number of members in this guild
-    voice_states: Option<&'static [VoiceState]>, // * states of members currently in voice channels; lacks the guild_id key
-    members: Option<&'static [Member]>,          // * users in the guild
-    channels: Option<&'static [Channel]>,        // * channels in the guild
-    threads: Option<&'static [Channel]>, // * all active threads in the guild that current user has permission to view
-    presences: Option<&'static [PresenceUpdate]>, // * presences of the members in the guild, will only include non-offline members if the size is greater than large threshold
-    max_presences: Option<i32>, //the maximum number of presences for the guild (null is always returned, apart from the largest of guilds)
-    max_members: Option<i32>,   //the maximum number of members for the guild
-    vanity_url_code: &'static str, // the vanity url code for the guild
-    description: &'static str,  // the description of a Community guild
-    banner: &'static str,       // banner hash
-    premium_tier: i32,          // premium tier (Server Boost level)
-    premium_subscription_count: Option<i32>, //the number of boosts this guild currently has
-    preferred_locale: &'static str, // the preferred locale of a Community guild; used in server discovery and notices from Discord; defaults to "en-US"
-    public_updates_channel_id: Snowflake, // the id of the channel where admins and moderators of Community guilds receive notices from Discord
-    max_video_channel_users: Option<i32>, //the maximum amount of users in a video channel
-    approximate_member_count: Option<i32>, //approximate number of members in this guild, returned from the GET /guilds/<id> endpoint when with_counts is true
-    approximate_presence_count: Option<i32>, //approximate number of non-offline members in this guild, returned from the GET /guilds/<id> endpoint when with_counts is true
-    welcome_screen: Option<WelcomeScreen>, //the welcome screen of a Community guild, shown to new members, returned in an Invite's guild object
-    nsfw_level: i32,                       // guild NSFW level
-    stage_instances: Option<&'static [StageInstance]>, // * Stage instances in the guild
-    stickers: Option<&'static [Sticker]>,  //custom guild stickers
-    guild_scheduled_events: [events::GuildScheduledEvent], // * the scheduled events in the guild
-}
-
-#[derive(strum_macros::Display, Debug)]
-enum GuildFeature {
-    #[strum(serialize = "ANIMATED_ICON")]
-    AnimatedIcon, // guild has access to set an animated guild icon
-    #[strum(serialize = "BANNER")]
-    Banner, // guild has access to set a guild banner image
-    #[strum(serialize = "COMMERCE")]
-    Commerce, // guild has access to use commerce features (i.e. create store channels)
-    #[strum(serialize = "COMMUNITY")]
-    Community, // guild can enable welcome screen, Membership Screening, stage channels and discovery, and receives community updates
-    #[strum(serialize = "DISCOVERABLE")]
-    Discoverable, // guild is able to be discovered in the directory
-    #[strum(serialize = "FEATURABLE")]
-    Featurable, // guild is able to be featured in the directory
-    #[strum(serialize = "INVITE_SPLASH")]
-    InviteSplash, // guild has access to set an invite splash background
-    #[strum(serialize = "MEMBER_VERIFICATION_GATE_ENABLED")]
-    MemberVerificationGateEnabled, // guild has enabled Membership Screening
-    #[strum(serialize = "MONETIZATION_ENABLED")]
-    MonetizationEnabled, // guild has enabled monetization
-    #[strum(serialize = "MORE_STICKERS")]
-    MoreStickers, // guild has increased custom sticker slots
-    #[strum(serialize = "NEWS")]
-    News, // guild has access to create news channels
-    #[strum(serialize = "PARTNERED")]
-    Partnered, // guild is partnered
-    #[strum(serialize = "PREVIEW_ENABLED")]
-    PreviewEnabled, // guild can be previewed before joining via Membership Screening or the directory
-    #[strum(serialize = "PRIVATE_THREADS")]
-    PrivateThreads, // guild has access to create private threads
-    #[strum(serialize = "ROLE_ICONS")]
-    RoleIcons, // guild is able to set role icons
-    #[strum(serialize = "SEVEN_DAY_THREAD_ARCHIVE")]
-    SevenDayThreadArchive, // guild has access to the seven day archive time for threads
-    #[strum(serialize = "THREE_DAY_THREAD_ARCHIVE")]
-    ThreeDayThreadArchive, // guild has access to the three day archive time for threads
-    #[strum(serialize = "TICKETED_EVENTS_ENABLED")]
-    TicketedEventsEnabled, // guild has enabled ticketed events
-    #[strum(serialize = "VANITY_URL")]
-    VanityUrl, // guild has access to set a vanity URL
-    #[strum(serialize = "VERIFIED")]
-    Verified, // guild is verified
-    #[strum(serialize = "VIP_REGIONS")]
-    VIPRegions, // guild has access to set 384kbps bitrate in voice (previously VIP voice servers)
-    #[strum(serialize = "WELCOME_SCREEN_ENABLED")]
-    WelcomeScreenEnabled, // guild has enabled the welcome screen
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // guild id
+    pub name: String, // guild name (2-100 characters, excluding trailing and leading whitespace)
+    pub icon: Option<String>, // icon hash
+    pub icon_hash: Option<String>, // icon hash, returned when in the template object
+    pub splash: Option<String>, // splash hash
+    pub discovery_splash: Option<String>, // discovery splash hash; only present for guilds with the "DISCOVERABLE" feature
+    pub owner: Option<bool>, // ** true if the user is the owner of the guild
+    #[serde(with = "snowflakes::string")]
+    pub owner_id: Snowflake, // id of owner
+    pub permissions: Option<Permissions>, // ** total permissions for the user in the guild (excludes overwrites)
+    pub region: Option<String>, // *** voice region id for the guild (deprecated)
+    #[serde(default, with = "snowflakes::option")]
+    pub afk_channel_id: Option<Snowflake>, // id of afk channel
+    pub afk_timeout: i32, // afk timeout in seconds
+    pub widget_enabled: Option<bool>, // true if the server widget is enabled
+    #[serde(default, with = "snowflakes::option")]
+    pub widget_channel_id: Option<Snowflake>, // the channel id that the widget will generate an invite to, or null if set to no invite
+    pub verification_level: VerificationLevel, // verification level required for the guild
+    pub default_message_notifications: DefaultMessageNotificationLevel, // default message notifications level
+    pub explicit_content_filter: ExplicitContentFilterLevel, // explicit content filter level
+    #[serde(default)]
+    pub roles: Vec<roles::Role>, // roles in the guild
+    #[serde(default)]
+    pub emojis: Vec<Emoji>, // custom guild emojis
+    #[serde(default)]
+    pub features: Vec<GuildFeature>, // enabled guild features
+    pub mfa_level: MfaLevel, // required MFA level for the guild
+    #[serde(default, with = "snowflakes::option")]
+    pub application_id: Option<Snowflake>, // application id of the guild creator if it is bot-created
+    #[serde(default, with = "snowflakes::option")]
+    pub system_channel_id: Option<Snowflake>, // the id of the channel where guild notices such as welcome messages and boost events are posted
+    #[serde(default)]
+    pub system_channel_flags: SystemChannelFlags, // system channel flags
+    #[serde(default, with = "snowflakes::option")]
+    pub rules_channel_id: Option<Snowflake>, // the id of the channel where Community guilds can display rules and/or guidelines
+    pub joined_at: Option<DiscordTimestamp>, // * when this guild was joined at
+    pub large: Option<bool>, // * true if this is considered a large guild
+    pub unavailable: Option<bool>, // * true if this guild is unavailable due to an outage
+    pub member_count: Option<i32>, // * total number of members in this guild
+    pub voice_states: Option<Vec<VoiceState>>, // * states of members currently in voice channels; lacks the guild_id key
+    pub members: Option<Vec<Member>>, // * users in the guild
+    pub channels: Option<Vec<Channel>>, // * channels in the guild
+    pub threads: Option<Vec<Channel>>, // * all active threads in the guild that current user has permission to view
+    pub presences: Option<Vec<PresenceUpdate>>, // * presences of the members in the guild, will only include non-offline members if the size is greater than large threshold
+    pub max_presences: Option<i32>, // the maximum number of presences for the guild (null is always returned, apart from the largest of guilds)
+    pub max_members: Option<i32>, // the maximum number of members for the guild
+    pub vanity_url_code: Option<String>, // the vanity url code for the guild
+    pub description: Option<String>, // the description of a Community guild
+    pub banner: Option<String>, // banner hash
+    pub premium_tier: PremiumTier, // premium tier (Server Boost level)
+    pub premium_subscription_count: Option<i32>, // the number of boosts this guild currently has
+    #[serde(default)]
+    pub preferred_locale: String, // the preferred locale of a Community guild; used in server discovery and notices from Discord; defaults to "en-US"
+    #[serde(default, with = "snowflakes::option")]
+    pub public_updates_channel_id: Option<Snowflake>, // the id of the channel where admins and moderators of Community guilds receive notices from Discord
+    pub max_video_channel_users: Option<i32>, // the maximum amount of users in a video channel
+    pub approximate_member_count: Option<i32>, // approximate number of members in this guild, returned from the GET /guilds/<id> endpoint when with_counts is true
+    pub approximate_presence_count: Option<i32>, // approximate number of non-offline members in this guild, returned from the GET /guilds/<id> endpoint when with_counts is true
+    pub welcome_screen: Option<WelcomeScreen>, // the welcome screen of a Community guild, shown to new members, returned in an Invite's guild object
+    pub nsfw_level: NsfwLevel, // guild NSFW level
+    pub stage_instances: Option<Vec<StageInstance>>, // * Stage instances in the guild
+    pub stickers: Option<Vec<Sticker>>, // custom guild stickers
+    #[serde(default)]
+    pub guild_scheduled_events: Vec<events::GuildScheduledEvent>, // * the scheduled events in the guild
+    #[serde(default)]
+    pub premium_progress_bar_enabled: bool, // whether the guild has the boost progress bar enabled
+}
+
+str_enum!(GuildFeature {
+    AnimatedIcon = "ANIMATED_ICON", // guild has access to set an animated guild icon
+    Banner = "BANNER", // guild has access to set a guild banner image
+    Commerce = "COMMERCE", // guild has access to use commerce features (i.e. create store channels)
+    Community = "COMMUNITY", // guild can enable welcome screen, Membership Screening, stage channels and discovery, and receives community updates
+    Discoverable = "DISCOVERABLE", // guild is able to be discovered in the directory
+    Featurable = "FEATURABLE", // guild is able to be featured in the directory
+    InviteSplash = "INVITE_SPLASH", // guild has access to set an invite splash background
+    MemberVerificationGateEnabled = "MEMBER_VERIFICATION_GATE_ENABLED", // guild has enabled Membership Screening
+    MonetizationEnabled = "MONETIZATION_ENABLED", // guild has enabled monetization
+    MoreStickers = "MORE_STICKERS", // guild has increased custom sticker slots
+    News = "NEWS", // guild has access to create news channels
+    Partnered = "PARTNERED", // guild is partnered
+    PreviewEnabled = "PREVIEW_ENABLED", // guild can be previewed before joining via Membership Screening or the directory
+    PrivateThreads = "PRIVATE_THREADS", // guild has access to create private threads
+    RoleIcons = "ROLE_ICONS", // guild is able to set role icons
+    SevenDayThreadArchive = "SEVEN_DAY_THREAD_ARCHIVE", // guild has access to the seven day archive time for threads
+    ThreeDayThreadArchive = "THREE_DAY_THREAD_ARCHIVE", // guild has access to the three day archive time for threads
+    TicketedEventsEnabled = "TICKETED_EVENTS_ENABLED", // guild has enabled ticketed events
+    VanityUrl = "VANITY_URL", // guild has access to set a vanity URL
+    Verified = "VERIFIED", // guild is verified
+    VIPRegions = "VIP_REGIONS", // guild has access to set 384kbps bitrate in voice (previously VIP voice servers)
+    WelcomeScreenEnabled = "WELCOME_SCREEN_ENABLED", // guild has enabled the welcome screen
+});
+
+int_enum!(VerificationLevel: u8 {
+    None = 0,     // unrestricted
+    Low = 1,      // must have verified email on account
+    Medium = 2,   // must be registered on Discord for longer than 5 minutes
+    High = 3,     // must be a member of the server for longer than 10 minutes
+    VeryHigh = 4, // must have a verified phone number
+});
+
+int_enum!(DefaultMessageNotificationLevel: u8 {
+    AllMessages = 0,  // members will receive notifications for all messages by default
+    OnlyMentions = 1, // members will receive notifications only for messages that @mention them by default
+});
+
+int_enum!(ExplicitContentFilterLevel: u8 {
+    Disabled = 0,            // media content will not be scanned
+    MembersWithoutRoles = 1, // media content sent by members without roles will be scanned
+    AllMembers = 2,          // media content sent by all members will be scanned
+});
+
+int_enum!(MfaLevel: u8 {
+    None = 0,     // guild has no MFA/2FA requirement for moderation actions
+    Elevated = 1, // guild has a 2FA requirement for moderation actions
+});
+
+pub const DEFAULT_UPLOAD_LIMIT: u64 = 10 * 1024 * 1024; // upload limit in dms and unboosted guilds
+
+int_enum!(PremiumTier: u8 {
+    None = 0,  // guild has not unlocked any Server Boost perks
+    Tier1 = 1, // guild has unlocked Server Boost level 1 perks
+    Tier2 = 2, // guild has unlocked Server Boost level 2 perks
+    Tier3 = 3, // guild has unlocked Server Boost level 3 perks
+});
+
+int_enum!(NsfwLevel: u8 {
+    Default = 0,
+    Explicit = 1,
+    Safe = 2,
+    AgeRestricted = 3,
+});
+
+flags!(SystemChannelFlags: u64 {
+    SUPPRESS_JOIN_NOTIFICATIONS = 0, // suppress member join notifications
+    SUPPRESS_PREMIUM_SUBSCRIPTIONS = 1, // suppress server boost notifications
+    SUPPRESS_GUILD_REMINDER_NOTIFICATIONS = 2, // suppress server setup tips
+    SUPPRESS_JOIN_NOTIFICATION_REPLIES = 3, // hide member join sticker reply buttons
+    SUPPRESS_ROLE_SUBSCRIPTION_PURCHASE_NOTIFICATIONS = 4, // suppress role subscription purchase and renewal notifications
+    SUPPRESS_ROLE_SUBSCRIPTION_PURCHASE_NOTIFICATION_REPLIES = 5, // hide role subscription sticker reply buttons
+});
+
+// the names shown in the Discord client's server settings
+impl VerificationLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            VerificationLevel::None => "None",
+            VerificationLevel::Low => "Low",
+            VerificationLevel::Medium => "Medium",
+            VerificationLevel::High => "High",
+            VerificationLevel::VeryHigh => "Highest",
+            VerificationLevel::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl DefaultMessageNotificationLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            DefaultMessageNotificationLevel::AllMessages => "All Messages",
+            DefaultMessageNotificationLevel::OnlyMentions => "Only @mentions",
+            DefaultMessageNotificationLevel::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl ExplicitContentFilterLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            ExplicitContentFilterLevel::Disabled => "Do not filter",
+            ExplicitContentFilterLevel::MembersWithoutRoles => {
+                "Filter messages from server members without roles"
+            }
+            ExplicitContentFilterLevel::AllMembers => "Filter messages from all members",
+            ExplicitContentFilterLevel::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl MfaLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            MfaLevel::None => "Not required",
+            MfaLevel::Elevated => "2FA required for moderation",
+            MfaLevel::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl PremiumTier {
+    pub fn name(self) -> &'static str {
+        match self {
+            PremiumTier::None => "No Level",
+            PremiumTier::Tier1 => "Level 1",
+            PremiumTier::Tier2 => "Level 2",
+            PremiumTier::Tier3 => "Level 3",
+            PremiumTier::Unknown(_) => "Unknown",
+        }
+    }
+
+    // the most bytes a message can upload in a guild at this tier
+    pub fn upload_limit(self) -> u64 {
+        match self {
+            PremiumTier::Tier2 => 50 * 1024 * 1024,
+            PremiumTier::Tier3 => 100 * 1024 * 1024,
+            _ => DEFAULT_UPLOAD_LIMIT,
+        }
+    }
+}
+
+impl Guild {
+    pub fn upload_limit(&self) -> u64 {
+        self.premium_tier.upload_limit()
+    }
+}
+
+impl NsfwLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            NsfwLevel::Default => "Default",
+            NsfwLevel::Explicit => "Explicit",
+            NsfwLevel::Safe => "Safe",
+            NsfwLevel::AgeRestricted => "Age-Restricted",
+            NsfwLevel::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl SystemChannelFlags {
+    const NAMES: [(SystemChannelFlags, &'static str); 6] = [
+        (
+            SystemChannelFlags::SUPPRESS_JOIN_NOTIFICATIONS,
+            "Suppress join notifications",
+        ),
+        (
+            SystemChannelFlags::SUPPRESS_PREMIUM_SUBSCRIPTIONS,
+            "Suppress boost notifications",
+        ),
+        (
+            SystemChannelFlags::SUPPRESS_GUILD_REMINDER_NOTIFICATIONS,
+            "Suppress server setup tips",
+        ),
+        (
+            SystemChannelFlags::SUPPRESS_JOIN_NOTIFICATION_REPLIES,
+            "Hide join sticker replies",
+        ),
+        (
+            SystemChannelFlags::SUPPRESS_ROLE_SUBSCRIPTION_PURCHASE_NOTIFICATIONS,
+            "Suppress role subscription notifications",
+        ),
+        (
+            SystemChannelFlags::SUPPRESS_ROLE_SUBSCRIPTION_PURCHASE_NOTIFICATION_REPLIES,
+            "Hide role subscription sticker replies",
+        ),
+    ];
+
+    // names of the set flags, unknown bits are left out
+    pub fn names(self) -> Vec<&'static str> {
+        SystemChannelFlags::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+macro_rules! display_name {
+    ($($name:ident),*) => {
+        $(impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self {
+                    $name::Unknown(value) => write!(f, "Unknown ({})", value),
+                    _ => f.write_str(self.name()),
+                }
+            }
+        })*
+    };
+}
+
+display_name!(
+    VerificationLevel,
+    DefaultMessageNotificationLevel,
+    ExplicitContentFilterLevel,
+    MfaLevel,
+    PremiumTier,
+    NsfwLevel
+);
+
+impl fmt::Display for SystemChannelFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.names().as_slice() {
+            [] => f.write_str("None"),
+            names => f.write_str(&names.join(", ")),
+        }
+    }
 }
 
 pub mod roles {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::cmp::Ordering;
+
+    use super::snowflakes::{self, Snowflake};
+    use super::{Guild, Member, Permissions};
+    use crate::types::cdn::{self, ImageFormat};
+    use crate::types::permissions::effective;
+    use crate::types::permissions::Permission;
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
     pub struct Role {
-        id: super::Snowflake,
+        #[serde(with = "snowflakes::string")]
+        pub id: Snowflake, // role id
+        pub name: String, // role name
+        #[serde(default)]
+        pub color: i32, // integer representation of hexadecimal color code
+        #[serde(default)]
+        pub hoist: bool, // if this role is pinned in the user listing
+        pub icon: Option<String>, // role icon hash
+        pub unicode_emoji: Option<String>, // role unicode emoji
+        #[serde(default)]
+        pub position: i32, // position of this role (roles with the same position are sorted by id)
+        pub permissions: Permissions, // permission bit set
+        #[serde(default)]
+        pub managed: bool, // whether this role is managed by an integration
+        #[serde(default)]
+        pub mentionable: bool, // whether this role is mentionable
+        pub tags: Option<RoleTags>, // the tags this role has
+        #[serde(default)]
+        pub flags: i32, // role flags combined as a bitfield
     }
+
+    // the boolean tags are sent as null when true and left out when false
+    #[derive(Deserialize, Serialize, Clone, Default, Debug)]
+    pub struct RoleTags {
+        #[serde(
+            default,
+            with = "snowflakes::option",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub bot_id: Option<Snowflake>, // the id of the bot this role belongs to
+        #[serde(
+            default,
+            with = "snowflakes::option",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub integration_id: Option<Snowflake>, // the id of the integration this role belongs to
+        #[serde(
+            default,
+            deserialize_with = "present",
+            serialize_with = "null",
+            skip_serializing_if = "is_false"
+        )]
+        pub premium_subscriber: bool, // whether this is the guild's Booster role
+        #[serde(
+            default,
+            with = "snowflakes::option",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub subscription_listing_id: Option<Snowflake>, // the id of this role's subscription sku and listing
+        #[serde(
+            default,
+            deserialize_with = "present",
+            serialize_with = "null",
+            skip_serializing_if = "is_false"
+        )]
+        pub available_for_purchase: bool, // whether this role is available for purchase
+        #[serde(
+            default,
+            deserialize_with = "present",
+            serialize_with = "null",
+            skip_serializing_if = "is_false"
+        )]
+        pub guild_connections: bool, // whether this role is a guild's linked role
+    }
+
+    fn present<'de, D: Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+        Option::<()>::deserialize(d)?;
+        Ok(true)
+    }
+
+    fn null<S: Serializer>(_: &bool, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_none()
+    }
+
+    fn is_false(b: &bool) -> bool {
+        !b
+    }
+
+    impl Role {
+        pub fn mention(&self) -> String {
+            format!("<@&{}>", self.id)
+        }
+
+        pub fn icon_url(&self, format: ImageFormat, size: u16) -> Option<String> {
+            let hash = self.icon.as_deref()?;
+            Some(cdn::url(
+                &format!("role-icons/{}", self.id),
+                hash,
+                format,
+                size,
+            ))
+        }
+
+        // the @everyone role shares its id with the guild
+        pub fn is_everyone(&self, guild_id: Snowflake) -> bool {
+            self.id == guild_id
+        }
+
+        // orders roles as the client lists them: by position, and on equal
+        // positions the older role (lower id) ranks higher
+        pub fn hierarchy_cmp(&self, other: &Role) -> Ordering {
+            self.position
+                .cmp(&other.position)
+                .then_with(|| other.id.cmp(&self.id))
+        }
+    }
+
+    impl Guild {
+        pub fn role(&self, id: Snowflake) -> Option<&Role> {
+            self.roles.iter().find(|r| r.id == id)
+        }
+
+        pub fn everyone_role(&self) -> Option<&Role> {
+            self.role(self.id)
+        }
+
+        // the member's top role, or @everyone when they have none
+        pub fn highest_role(&self, member: &Member) -> Option<&Role> {
+            self.roles
+                .iter()
+                .filter(|r| member.roles.contains(&r.id))
+                .max_by(|a, b| a.hierarchy_cmp(b))
+                .or_else(|| self.everyone_role())
+        }
+
+        // roles sorted from the top of the hierarchy down
+        pub fn roles_by_hierarchy(&self) -> Vec<&Role> {
+            let mut roles: Vec<&Role> = self.roles.iter().collect();
+            roles.sort_by(|a, b| b.hierarchy_cmp(a));
+            roles
+        }
+
+        pub fn is_owner(&self, user_id: Snowflake) -> bool {
+            user_id == self.owner_id
+        }
+
+        // whether a's highest role is above b's; the owner outranks everyone. the
+        // user ids are passed on their own, as the members in MESSAGE_CREATE and
+        // MESSAGE_UPDATE come without their user
+        pub fn outranks(&self, a_id: Snowflake, a: &Member, b_id: Snowflake, b: &Member) -> bool {
+            if self.is_owner(b_id) {
+                return false;
+            }
+            if self.is_owner(a_id) {
+                return true;
+            }
+            match (self.highest_role(a), self.highest_role(b)) {
+                (Some(a), Some(b)) => a.hierarchy_cmp(b) == Ordering::Greater,
+                (Some(_), None) => true,
+                _ => false,
+            }
+        }
+
+        // whether the member may edit, assign or delete the role: they need Manage
+        // Roles and their highest role must be above it
+        pub fn can_manage_role(
+            &self,
+            user_id: Snowflake,
+            member: &Member,
+            role_id: Snowflake,
+        ) -> bool {
+            let role = match self.role(role_id) {
+                Some(role) => role,
+                None => return false,
+            };
+            if self.is_owner(user_id) {
+                return true;
+            }
+            let permissions = effective::guild_permissions(self, user_id, member);
+            if !permissions.contains(Permission::ManageRoles) {
+                return false;
+            }
+            self.highest_role(member)
+                .is_some_and(|top| top.hierarchy_cmp(role) == Ordering::Greater)
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct WelcomeScreen {
+    pub description: Option<String>, // the server description shown in the welcome screen
+    #[serde(default)]
+    pub welcome_channels: Vec<WelcomeScreenChannel>, // the channels shown in the welcome screen, up to 5
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct WelcomeScreenChannel {
+    #[serde(with = "snowflakes::string")]
+    pub channel_id: Snowflake, // the channel's id
+    pub description: String, // the description shown for the channel
+    #[serde(default, with = "snowflakes::option")]
+    pub emoji_id: Option<Snowflake>, // the emoji id, if the emoji is custom
+    pub emoji_name: Option<String>, // the emoji name if custom, the unicode character if standard, or null if no emoji is set
+}
+
+// a public preview of a discoverable guild, available without joining it
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GuildPreview {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // guild id
+    pub name: String, // guild name (2-100 characters)
+    pub icon: Option<String>, // icon hash
+    pub splash: Option<String>, // splash hash
+    pub discovery_splash: Option<String>, // discovery splash hash
+    #[serde(default)]
+    pub emojis: Vec<Emoji>, // custom guild emojis
+    #[serde(default)]
+    pub features: Vec<GuildFeature>, // enabled guild features
+    pub approximate_member_count: i32, // approximate number of members in this guild
+    pub approximate_presence_count: i32, // approximate number of online members in this guild
+    pub description: Option<String>, // the description for the guild
+    #[serde(default)]
+    pub stickers: Vec<Sticker>, // custom guild stickers
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GuildWidgetSettings {
+    pub enabled: bool, // whether the widget is enabled
+    #[serde(default, with = "snowflakes::option")]
+    pub channel_id: Option<Snowflake>, // the widget channel id
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct VanityUrl {
+    pub code: Option<String>, // the vanity invite code, null when the guild has none
+    #[serde(default)]
+    pub uses: i32, // how many times the vanity invite has been used
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct VoiceRegion {
+    pub id: String,       // unique ID for the region
+    pub name: String,     // name of the region
+    pub optimal: bool,    // true for a single server that is closest to the current user's client
+    pub deprecated: bool, // whether this is a deprecated voice region (avoid switching to these)
+    pub custom: bool,     // whether this is a custom voice region (used for events/etc)
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Integration {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // integration id
+    pub name: String, // integration name
+    #[serde(rename = "type")]
+    pub integration_type: String, // integration type (twitch, youtube, discord, or guild_subscription)
+    pub enabled: Option<bool>, // is this integration enabled
+    pub syncing: Option<bool>, // is this integration syncing
+    #[serde(default, with = "snowflakes::option")]
+    pub role_id: Option<Snowflake>, // id that this integration uses for "subscribers"
+    pub enable_emoticons: Option<bool>, // whether emoticons should be synced for this integration (twitch only currently)
+    pub expire_behavior: Option<i32>, // the behavior of expiring subscribers
+    pub expire_grace_period: Option<i32>, // the grace period (in days) before expiring subscribers
+    pub user: Option<User>, // user for this integration
+    pub account: IntegrationAccount, // integration account information
+    pub synced_at: Option<DiscordTimestamp>, // when this integration was last synced
+    pub subscriber_count: Option<i32>, // how many subscribers this integration has
+    pub revoked: Option<bool>, // has this integration been revoked
+    pub application: Option<IntegrationApplication>, // the bot/OAuth2 application for discord integrations
+    #[serde(default)]
+    pub scopes: Vec<String>, // the scopes the application has been authorized for
 }
 
-struct WelcomeScreen {
-    description: &'static str,
-    welcome_channels: &'static [WelcomeScreenChannel],
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct IntegrationAccount {
+    pub id: String,   // id of the account
+    pub name: String, // name of the account
 }
 
-struct WelcomeScreenChannel {
-    channel_id: Snowflake,
-    description: &'static str,
-    emoji_id: Snowflake,
-    emoji_name: &'static str,
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct IntegrationApplication {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // the id of the app
+    pub name: String, // the name of the app
+    pub icon: Option<String>, // the icon hash of the app
+    pub description: String, // the description of the app
+    pub bot: Option<User>, // the bot associated with this application
 }
 
 pub mod events {
-    use super::{super::DiscordTimestamp, Snowflake};
+    use super::{super::snowflakes, super::DiscordTimestamp, Snowflake};
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+    use serde_json::Value;
 
-    pub enum PrivacyLevel {
+    int_enum!(PrivacyLevel: u8 {
         GuildOnly = 2,
-    }
+    });
 
-    pub enum Status {
+    int_enum!(Status: u8 {
         Scheduled = 1,
         Active = 2,
         Completed = 3,
         Canceled = 4,
-    }
+    });
 
-    struct EntityMetadata {
-        location: &'static str,
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EntityMetadata {
+        pub location: String,
     }
 
-    struct Common {
-        id: Snowflake,
-        guild_id: Snowflake,
-        creator_id: Option<Snowflake>,
-        name: &'static str,
-        description: Option<&'static str>,
-        scheduled_start_time: DiscordTimestamp,
-        privacy_level: PrivacyLevel,
-        status: Status,
-        entity_id: Option<Snowflake>,
-        creator: Option<super::super::users::User>,
-        user_count: i32,
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct Common {
+        #[serde(with = "snowflakes::string")]
+        pub id: Snowflake,
+        #[serde(with = "snowflakes::string")]
+        pub guild_id: Snowflake,
+        #[serde(default, with = "snowflakes::option")]
+        pub creator_id: Option<Snowflake>,
+        pub name: String,
+        pub description: Option<String>,
+        pub scheduled_start_time: DiscordTimestamp,
+        pub privacy_level: PrivacyLevel,
+        pub status: Status,
+        #[serde(default, with = "snowflakes::option")]
+        pub entity_id: Option<Snowflake>,
+        pub creator: Option<super::super::users::User>,
+        #[serde(default)]
+        pub user_count: i32,
     }
 
+    #[derive(Clone, Debug)]
     pub enum GuildScheduledEvent {
         StageInstance {
             d: Common,
             channel_id: Snowflake,
-            scheduled_end_time: DiscordTimestamp,
+            scheduled_end_time: Option<DiscordTimestamp>,
         },
         Voice {
             d: Common,
             channel_id: Snowflake,
-            scheduled_end_time: DiscordTimestamp,
+            scheduled_end_time: Option<DiscordTimestamp>,
         },
         External {
             d: Common,
             entity_metadata: EntityMetadata,
             scheduled_end_time: Option<DiscordTimestamp>,
         },
+        // an entity type this library doesn't know yet, kept as sent
+        Unknown {
+            d: Common,
+            entity_type: u8,
+            channel_id: Option<Snowflake>,
+            entity_metadata: Option<Value>,
+            scheduled_end_time: Option<DiscordTimestamp>,
+        },
+    }
+
+    impl GuildScheduledEvent {
+        pub fn common(&self) -> &Common {
+            match self {
+                GuildScheduledEvent::StageInstance { d, .. }
+                | GuildScheduledEvent::Voice { d, .. }
+                | GuildScheduledEvent::External { d, .. }
+                | GuildScheduledEvent::Unknown { d, .. } => d,
+            }
+        }
+    }
+
+    // the api sends one flat object and tells the variants apart by entity_type
+    #[derive(Deserialize, Serialize)]
+    struct RawEvent {
+        #[serde(flatten)]
+        d: Common,
+        entity_type: u8,
+        #[serde(default, with = "snowflakes::option")]
+        channel_id: Option<Snowflake>,
+        entity_metadata: Option<Value>,
+        scheduled_end_time: Option<DiscordTimestamp>,
+    }
+
+    impl<'de> Deserialize<'de> for GuildScheduledEvent {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            let raw = RawEvent::deserialize(d)?;
+            let channel_id = raw
+                .channel_id
+                .ok_or_else(|| de::Error::missing_field("channel_id"));
+            Ok(match raw.entity_type {
+                1 => GuildScheduledEvent::StageInstance {
+                    d: raw.d,
+                    channel_id: channel_id?,
+                    scheduled_end_time: raw.scheduled_end_time,
+                },
+                2 => GuildScheduledEvent::Voice {
+                    d: raw.d,
+                    channel_id: channel_id?,
+                    scheduled_end_time: raw.scheduled_end_time,
+                },
+                3 => GuildScheduledEvent::External {
+                    d: raw.d,
+                    entity_metadata: serde_json::from_value(
+                        raw.entity_metadata
+                            .ok_or_else(|| de::Error::missing_field("entity_metadata"))?,
+                    )
+                    .map_err(de::Error::custom)?,
+                    scheduled_end_time: raw.scheduled_end_time,
+                },
+                entity_type => GuildScheduledEvent::Unknown {
+                    d: raw.d,
+                    entity_type,
+                    channel_id: raw.channel_id,
+                    entity_metadata: raw.entity_metadata,
+                    scheduled_end_time: raw.scheduled_end_time,
+                },
+            })
+        }
+    }
+
+    impl Serialize for GuildScheduledEvent {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            let raw = match self.clone() {
+                GuildScheduledEvent::StageInstance {
+                    d,
+                    channel_id,
+                    scheduled_end_time,
+                } => RawEvent {
+                    d,
+                    entity_type: 1,
+                    channel_id: Some(channel_id),
+                    entity_metadata: None,
+                    scheduled_end_time,
+                },
+                GuildScheduledEvent::Voice {
+                    d,
+                    channel_id,
+                    scheduled_end_time,
+                } => RawEvent {
+                    d,
+                    entity_type: 2,
+                    channel_id: Some(channel_id),
+                    entity_metadata: None,
+                    scheduled_end_time,
+                },
+                GuildScheduledEvent::External {
+                    d,
+                    entity_metadata,
+                    scheduled_end_time,
+                } => RawEvent {
+                    d,
+                    entity_type: 3,
+                    channel_id: None,
+                    entity_metadata: Some(
+                        serde_json::to_value(entity_metadata).map_err(ser::Error::custom)?,
+                    ),
+                    scheduled_end_time,
+                },
+                GuildScheduledEvent::Unknown {
+                    d,
+                    entity_type,
+                    channel_id,
+                    entity_metadata,
+                    scheduled_end_time,
+                } => RawEvent {
+                    d,
+                    entity_type,
+                    channel_id,
+                    entity_metadata,
+                    scheduled_end_time,
+                },
+            };
+            raw.serialize(s)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::events::GuildScheduledEvent;
+    use super::Guild;
+    use crate::types::permissions::{Permission, Permissions};
+    use crate::types::snowflakes::Snowflake;
+    use crate::types::users::Member;
+    use serde_json::json;
+
+    const GUILD: Snowflake = 1;
+    const OWNER: Snowflake = 99;
+
+    // roles as (id, position, permissions), after the @everyone role
+    fn guild(roles: &[(Snowflake, i32, Permissions)]) -> Guild {
+        let roles: Vec<_> = [(GUILD, 0, Permissions::empty())]
+            .iter()
+            .chain(roles)
+            .map(|(id, position, permissions)| {
+                json!({
+                    "id": id.to_string(),
+                    "name": "role",
+                    "position": position,
+                    "permissions": permissions.bits().to_string(),
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "id": GUILD.to_string(),
+            "name": "guild",
+            "owner_id": OWNER.to_string(),
+            "afk_timeout": 300,
+            "verification_level": 0,
+            "default_message_notifications": 0,
+            "explicit_content_filter": 0,
+            "roles": roles,
+            "emojis": [],
+            "features": [],
+            "mfa_level": 0,
+            "system_channel_flags": 0,
+            "premium_tier": 0,
+            "preferred_locale": "en-US",
+            "nsfw_level": 0,
+            "guild_scheduled_events": [],
+            "premium_progress_bar_enabled": false,
+        }))
+        .unwrap()
+    }
+
+    // as sent in MESSAGE_CREATE, without the user
+    fn member(roles: &[Snowflake]) -> Member {
+        let roles: Vec<_> = roles.iter().map(|id| id.to_string()).collect();
+        serde_json::from_value(json!({ "roles": roles })).unwrap()
+    }
+
+    #[test]
+    fn position_ties_are_broken_by_the_older_role() {
+        let none = Permissions::empty();
+        let guild = guild(&[(11, 1, none), (10, 1, none), (12, 2, none)]);
+        let (older, newer) = (guild.role(10).unwrap(), guild.role(11).unwrap());
+        assert_eq!(older.hierarchy_cmp(newer), Ordering::Greater);
+        assert_eq!(newer.hierarchy_cmp(older), Ordering::Less);
+
+        assert_eq!(guild.highest_role(&member(&[11, 10])).unwrap().id, 10);
+        assert_eq!(guild.highest_role(&member(&[11, 10, 12])).unwrap().id, 12);
+        let ids: Vec<_> = guild.roles_by_hierarchy().iter().map(|r| r.id).collect();
+        assert_eq!(ids, [12, 10, 11, GUILD]);
+        assert!(guild.outranks(5, &member(&[10]), 6, &member(&[11])));
+        assert!(!guild.outranks(6, &member(&[11]), 5, &member(&[10])));
+    }
+
+    #[test]
+    fn the_owner_outranks_everyone_and_manages_every_role() {
+        let guild = guild(&[(10, 5, Permission::Administrator.into())]);
+        let owner = member(&[]);
+        let admin = member(&[10]);
+
+        assert!(guild.is_owner(OWNER));
+        assert!(guild.outranks(OWNER, &owner, 5, &admin));
+        assert!(!guild.outranks(5, &admin, OWNER, &owner));
+        assert!(guild.can_manage_role(OWNER, &owner, 10));
+        assert!(!guild.can_manage_role(OWNER, &owner, 404));
+    }
+
+    #[test]
+    fn a_member_without_roles_ranks_at_everyone() {
+        let guild = guild(&[(10, 1, Permission::ManageRoles.into())]);
+        let plain = member(&[]);
+
+        assert_eq!(guild.highest_role(&plain).unwrap().id, GUILD);
+        assert!(!guild.outranks(5, &plain, 6, &member(&[])));
+        assert!(!guild.outranks(5, &plain, 6, &member(&[10])));
+        assert!(guild.outranks(6, &member(&[10]), 5, &plain));
+        assert!(!guild.can_manage_role(5, &plain, GUILD));
+    }
+
+    #[test]
+    fn managing_a_role_needs_manage_roles_and_a_higher_role() {
+        let guild = guild(&[
+            (10, 1, Permissions::empty()),
+            (11, 2, Permissions::empty()),
+            (12, 3, Permission::ManageRoles.into()),
+        ]);
+
+        // above the role, but without Manage Roles
+        assert!(!guild.can_manage_role(5, &member(&[11]), 10));
+
+        let manager = member(&[12]);
+        assert!(guild.can_manage_role(5, &manager, 10));
+        assert!(guild.can_manage_role(5, &manager, 11));
+        assert!(!guild.can_manage_role(5, &manager, 12));
+    }
+
+    #[test]
+    fn scheduled_event_keeps_an_unknown_entity_type() {
+        let raw = json!({
+            "id": "1",
+            "guild_id": "2",
+            "name": "event",
+            "description": null,
+            "scheduled_start_time": "2022-01-01T00:00:00Z",
+            "privacy_level": 2,
+            "status": 1,
+            "entity_type": 9,
+            "channel_id": "3",
+            "entity_metadata": { "speakers": ["4"] },
+            "scheduled_end_time": null,
+        });
+        let event: GuildScheduledEvent = serde_json::from_value(raw).unwrap();
+        match &event {
+            GuildScheduledEvent::Unknown {
+                entity_type,
+                channel_id,
+                entity_metadata,
+                ..
+            } => {
+                assert_eq!(*entity_type, 9);
+                assert_eq!(*channel_id, Some(3));
+                assert_eq!(entity_metadata, &Some(json!({ "speakers": ["4"] })));
+            }
+            event => panic!("parsed as {:?}", event),
+        }
+        assert_eq!(event.common().name, "event");
+
+        let back = serde_json::to_value(&event).unwrap();
+        assert_eq!(back["entity_type"], 9);
+        assert_eq!(back["channel_id"], "3");
+        assert_eq!(back["entity_metadata"], json!({ "speakers": ["4"] }));
     }
 }
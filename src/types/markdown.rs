@@ -0,0 +1,805 @@
+use super::cdn;
+use super::content::{token_at, TimestampStyle, TokenKind};
+
+// discord's flavour of markdown, parsed the way the client does: code first,
+// then escapes, links and mentions, then the formatting delimiters. formatting
+// only applies within a block; quotes, headings and lists start at a line
+#[derive(Clone, Debug)]
+pub enum Node {
+    Text(String),
+    Bold(Vec<Node>),          // **text**
+    Italic(Vec<Node>),        // *text* or _text_
+    Underline(Vec<Node>),     // __text__
+    Strikethrough(Vec<Node>), // ~~text~~
+    Spoiler(Vec<Node>),       // ||text||
+    InlineCode(String),       // `code` or ``code``
+    CodeBlock {
+        language: Option<String>,
+        code: String,
+    }, // ```lang\ncode```
+    BlockQuote(Vec<Node>),    // lines starting with "> ", or everything after ">>> "
+    Heading {
+        level: u8, // 1-3
+        children: Vec<Node>,
+    }, // # text
+    List {
+        start: Option<u64>, // the first number of a numbered list
+        items: Vec<Vec<Node>>,
+    }, // lines starting with "- ", "* " or "1. "
+    Link {
+        url: String,
+        children: Vec<Node>,
+    }, // [text](url)
+    Url(String),              // a bare http(s) link
+    Token(TokenKind),         // a mention, command, timestamp or custom emoji
+}
+
+pub fn parse(content: &str) -> Vec<Node> {
+    parse_blocks(content, true)
+}
+
+// renders the nodes as html, escaping all text. `resolve` names mentions, such
+// as "@name" for a user; when it returns None the raw mention is shown
+pub fn to_html(nodes: &[Node], resolve: impl Fn(&TokenKind) -> Option<String>) -> String {
+    let mut out = String::new();
+    html(nodes, &resolve, &mut out);
+    out
+}
+
+// renders the nodes as the text a reader sees, without markdown
+pub fn to_plain_text(nodes: &[Node], resolve: impl Fn(&TokenKind) -> Option<String>) -> String {
+    let mut out = String::new();
+    plain(nodes, &resolve, &mut out);
+    out
+}
+
+fn parse_blocks(text: &str, quotes: bool) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut para = 0; // start of the text not in a block yet
+    let mut i = 0;
+    while i < text.len() {
+        match block_at(text, i, quotes) {
+            Some((node, end)) => {
+                let before = &text[para..i];
+                nodes.extend(parse_inline(before.strip_suffix('\n').unwrap_or(before)));
+                nodes.push(node);
+                i = end;
+                para = end;
+            }
+            None => i = next_line(text, i),
+        }
+    }
+    nodes.extend(parse_inline(&text[para..]));
+    nodes
+}
+
+// the start of the next line, skipping over code blocks so their lines are
+// never read as quotes, headings or lists
+fn next_line(text: &str, mut i: usize) -> usize {
+    loop {
+        let newline = text[i..].find('\n').map(|n| i + n);
+        let fence = text[i..].find("```").map(|n| i + n);
+        match (newline, fence) {
+            (Some(newline), Some(fence)) if fence < newline => {
+                match text[fence + 3..].find("```") {
+                    Some(close) => i = fence + 3 + close + 3,
+                    None => return newline + 1,
+                }
+            }
+            (Some(newline), _) => return newline + 1,
+            (None, _) => return text.len(),
+        }
+    }
+}
+
+fn line_end(text: &str, i: usize) -> usize {
+    text[i..].find('\n').map_or(text.len(), |n| i + n)
+}
+
+// the block starting at the line at i, and where it ends
+fn block_at(text: &str, i: usize, quotes: bool) -> Option<(Node, usize)> {
+    let end = line_end(text, i);
+    let line = text[i..end].trim_start_matches(' ');
+    let next = (end + 1).min(text.len());
+
+    if quotes {
+        if line.starts_with(">>> ") {
+            let quoted = &text[end - line.len() + 4..];
+            return Some((Node::BlockQuote(parse_blocks(quoted, false)), text.len()));
+        }
+        if line.starts_with("> ") {
+            let mut lines = Vec::new();
+            let mut at = i;
+            while at < text.len() {
+                let end = line_end(text, at);
+                match text[at..end].trim_start_matches(' ').strip_prefix("> ") {
+                    Some(quoted) => lines.push(quoted),
+                    None => break,
+                }
+                at = (end + 1).min(text.len());
+            }
+            let quoted = lines.join("\n");
+            return Some((Node::BlockQuote(parse_blocks(&quoted, false)), at));
+        }
+    }
+
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    if (1..=3).contains(&hashes) {
+        if let Some(title) = line[hashes..].strip_prefix(' ') {
+            let title = title.trim();
+            if !title.is_empty() {
+                let children = parse_inline(title);
+                return Some((
+                    Node::Heading {
+                        level: hashes as u8,
+                        children,
+                    },
+                    next,
+                ));
+            }
+        }
+    }
+
+    let (first, start) = list_item(line)?;
+    let mut items = vec![parse_inline(first)];
+    let mut at = next;
+    while at < text.len() {
+        let end = line_end(text, at);
+        match list_item(text[at..end].trim_start_matches(' ')) {
+            Some((item, n)) if n.is_some() == start.is_some() => items.push(parse_inline(item)),
+            _ => break,
+        }
+        at = (end + 1).min(text.len());
+    }
+    Some((Node::List { start, items }, at))
+}
+
+// the text of a list item line, and its number if it is numbered
+fn list_item(line: &str) -> Option<(&str, Option<u64>)> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some((item.trim(), None)).filter(|(item, _)| !item.is_empty());
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    let item = line[digits..].strip_prefix(". ")?.trim();
+    Some((item, line[..digits].parse().ok())).filter(|(item, _)| !item.is_empty())
+}
+
+fn parse_inline(text: &str) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if rest.starts_with('`') {
+            match code_block(rest).or_else(|| inline_code(rest)) {
+                Some((node, len)) => {
+                    flush(&mut nodes, &mut plain);
+                    i += len;
+                    // the line break after a code block is part of the block
+                    if is_block(&node) && text[i..].starts_with('\n') {
+                        i += 1;
+                    }
+                    nodes.push(node);
+                }
+                None => {
+                    // an unmatched run of backticks is text as a whole
+                    let run = rest.len() - rest.trim_start_matches('`').len();
+                    plain.push_str(&rest[..run]);
+                    i += run;
+                }
+            }
+            continue;
+        }
+
+        let mut chars = rest.chars();
+        let c = chars.next().unwrap_or_default();
+        if c == '\\' {
+            if let Some(escaped) = chars.next().filter(char::is_ascii_punctuation) {
+                plain.push(escaped);
+                i += 2;
+                continue;
+            }
+        }
+
+        match inline_at(text, i) {
+            Some((node, len)) => {
+                flush(&mut nodes, &mut plain);
+                nodes.push(node);
+                i += len;
+            }
+            None => {
+                plain.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    flush(&mut nodes, &mut plain);
+    nodes
+}
+
+fn flush(nodes: &mut Vec<Node>, plain: &mut String) {
+    if !plain.is_empty() {
+        nodes.push(Node::Text(std::mem::take(plain)));
+    }
+}
+
+// the node starting at i, other than code, and its length in bytes
+fn inline_at(text: &str, i: usize) -> Option<(Node, usize)> {
+    let rest = &text[i..];
+    let prev = text[..i].chars().next_back();
+    match rest.as_bytes()[0] {
+        b'[' => masked_link(rest),
+        b'<' => suppressed_url(rest).or_else(|| {
+            let token = token_at(text, i)?;
+            Some((Node::Token(token.kind), token.span.len()))
+        }),
+        b'h' if !prev.is_some_and(char::is_alphanumeric) => bare_url(rest),
+        b'*' => delimited(rest, "**", Node::Bold).or_else(|| star_italic(rest)),
+        b'_' => delimited(rest, "__", Node::Underline).or_else(|| underscore_italic(rest, prev)),
+        b'~' => delimited(rest, "~~", Node::Strikethrough),
+        b'|' => delimited(rest, "||", Node::Spoiler),
+        _ => None,
+    }
+}
+
+// ```code``` or ```lang\ncode```
+fn code_block(rest: &str) -> Option<(Node, usize)> {
+    let body = rest.strip_prefix("```")?;
+    let close = body.find("```")?;
+    let inner = &body[..close];
+    let (language, code) = match inner.split_once('\n') {
+        Some((language, code))
+            if !language.is_empty()
+                && language
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_+-.#".contains(c)) =>
+        {
+            (Some(language.to_string()), code)
+        }
+        _ => (None, inner),
+    };
+    let code = code.trim_matches('\n');
+    if code.trim().is_empty() {
+        return None;
+    }
+    let node = Node::CodeBlock {
+        language,
+        code: code.to_string(),
+    };
+    Some((node, close + 6))
+}
+
+// code between matching runs of backticks, trimmed like the client does
+fn inline_code(rest: &str) -> Option<(Node, usize)> {
+    let ticks = rest.len() - rest.trim_start_matches('`').len();
+    let body = &rest[ticks..];
+    let mut j = 0;
+    while let Some(k) = body[j..].find('`') {
+        let close = j + k;
+        let run = body[close..].len() - body[close..].trim_start_matches('`').len();
+        if run == ticks && close > 0 {
+            let code = body[..close].trim();
+            let code = if code.is_empty() {
+                &body[..close]
+            } else {
+                code
+            };
+            return Some((Node::InlineCode(code.to_string()), close + ticks * 2));
+        }
+        j = close + run;
+    }
+    None
+}
+
+// [text](url) or [text](<url>)
+fn masked_link(rest: &str) -> Option<(Node, usize)> {
+    // the label ends at the first ] not escaped with a backslash
+    let mut chars = rest.char_indices().skip(1);
+    let close = loop {
+        match chars.next()? {
+            (_, '\\') => {
+                chars.next();
+            }
+            (i, ']') => break i,
+            (_, '[' | '\n') => return None,
+            _ => {}
+        }
+    };
+    let label = &rest[1..close];
+    if label.trim().is_empty() || !rest[close..].starts_with("](") {
+        return None;
+    }
+    let target = &rest[close + 2..];
+    // like bare urls, the url keeps the ) of each ( inside it
+    let mut depth = 0;
+    let end = target.find(|c| {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return true,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        false
+    })?;
+    let url = target[..end].trim();
+    let url = url
+        .strip_prefix('<')
+        .and_then(|url| url.strip_suffix('>'))
+        .unwrap_or(url);
+    if !is_url(url) {
+        return None;
+    }
+    let node = Node::Link {
+        url: url.to_string(),
+        children: parse_inline(label),
+    };
+    Some((node, close + 2 + end + 1))
+}
+
+// <https://...>, a link without an embed
+fn suppressed_url(rest: &str) -> Option<(Node, usize)> {
+    let end = rest.find('>')?;
+    let url = &rest[1..end];
+    is_url(url).then(|| (Node::Url(url.to_string()), end + 1))
+}
+
+fn bare_url(rest: &str) -> Option<(Node, usize)> {
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c == '<')
+        .unwrap_or(rest.len());
+    // trailing punctuation belongs to the sentence, and so does a ) without a (
+    let mut url = rest[..end].trim_end_matches(['.', ',', ':', ';', '"', '\'', '!', '?']);
+    while url.ends_with(')') && url.matches('(').count() < url.matches(')').count() {
+        url = &url[..url.len() - 1];
+    }
+    is_url(url).then(|| (Node::Url(url.to_string()), url.len()))
+}
+
+fn is_url(url: &str) -> bool {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"));
+    rest.is_some_and(|rest| !rest.is_empty() && !url.contains(char::is_whitespace))
+}
+
+// delim text delim, closing at the first delim not followed by another of its
+// characters. code and escaped characters inside can't close it
+fn delimited(rest: &str, delim: &str, node: fn(Vec<Node>) -> Node) -> Option<(Node, usize)> {
+    let body = rest.strip_prefix(delim)?;
+    let repeat = &delim[..1];
+    let mut j = 0;
+    loop {
+        let at = &body[j..];
+        let c = at.chars().next()?;
+        if j > 0 && at.starts_with(delim) && !at[delim.len()..].starts_with(repeat) {
+            return Some((node(parse_inline(&body[..j])), j + delim.len() * 2));
+        } else if let Some(len) = code_len(at) {
+            j += len;
+        } else if c == '\\' {
+            j += 1 + at[1..].chars().next()?.len_utf8();
+        } else {
+            j += c.len_utf8();
+        }
+    }
+}
+
+// how far to skip over the code, or run of backticks that opens none, at the
+// start of at
+fn code_len(at: &str) -> Option<usize> {
+    if !at.starts_with('`') {
+        return None;
+    }
+    let run = at.len() - at.trim_start_matches('`').len();
+    Some(
+        code_block(at)
+            .or_else(|| inline_code(at))
+            .map_or(run, |(_, len)| len),
+    )
+}
+
+// *text*: it can't start with whitespace, and whitespace inside has to be
+// followed by something other than a lone *
+fn star_italic(rest: &str) -> Option<(Node, usize)> {
+    let body = rest.strip_prefix('*')?;
+    if body.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut j = 0;
+    loop {
+        let at = &body[j..];
+        let c = at.chars().next()?;
+        if at.starts_with("**") {
+            j += 2;
+        } else if c == '*' && j > 0 {
+            return Some((Node::Italic(parse_inline(&body[..j])), j + 2));
+        } else if c == '\\' {
+            j += 1 + at[1..].chars().next()?.len_utf8();
+        } else if c.is_whitespace() {
+            j += at.len() - at.trim_start().len();
+            let next = &body[j..];
+            if next.is_empty() || (next.starts_with('*') && !next.starts_with("**")) {
+                return None;
+            }
+        } else if c == '*' {
+            return None;
+        } else if let Some(len) = code_len(at) {
+            j += len;
+        } else {
+            j += c.len_utf8();
+        }
+    }
+}
+
+// _text_, only at word boundaries so snake_case names are left alone
+fn underscore_italic(rest: &str, prev: Option<char>) -> Option<(Node, usize)> {
+    if prev.is_some_and(is_word) {
+        return None;
+    }
+    let body = rest.strip_prefix('_')?;
+    let mut j = 0;
+    loop {
+        let at = &body[j..];
+        let c = at.chars().next()?;
+        if c == '_' && j > 0 && !at[1..].starts_with(is_word) {
+            return Some((Node::Italic(parse_inline(&body[..j])), j + 2));
+        } else if at.starts_with("__") {
+            j += 2;
+        } else if c == '\\' {
+            j += 1 + at[1..].chars().next()?.len_utf8();
+        } else if c == '_' {
+            return None;
+        } else if let Some(len) = code_len(at) {
+            j += len;
+        } else {
+            j += c.len_utf8();
+        }
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_block(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::CodeBlock { .. } | Node::BlockQuote(_) | Node::Heading { .. } | Node::List { .. }
+    )
+}
+
+// the text shown for a token: what resolve names it, or else the raw mention
+fn token_text(kind: &TokenKind, resolve: &dyn Fn(&TokenKind) -> Option<String>) -> String {
+    if let Some(text) = resolve(kind) {
+        return text;
+    }
+    match kind {
+        TokenKind::User(id) => format!("<@{}>", id),
+        TokenKind::Role(id) => format!("<@&{}>", id),
+        TokenKind::Channel(id) => format!("<#{}>", id),
+        TokenKind::Command { name, .. } => format!("/{}", name),
+        TokenKind::Timestamp { time, style } => {
+            let format = match style.unwrap_or(TimestampStyle::ShortDateTime) {
+                TimestampStyle::ShortTime => "%H:%M",
+                TimestampStyle::LongTime => "%H:%M:%S",
+                TimestampStyle::ShortDate => "%d/%m/%Y",
+                TimestampStyle::LongDate => "%-d %B %Y",
+                TimestampStyle::LongDateTime => "%A, %-d %B %Y %H:%M",
+                TimestampStyle::ShortDateTime | TimestampStyle::Relative => "%-d %B %Y %H:%M",
+            };
+            format!("{} UTC", time.format(format))
+        }
+        TokenKind::Emoji(emoji) => format!(":{}:", emoji.name.as_deref().unwrap_or("_")),
+    }
+}
+
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn html(nodes: &[Node], resolve: &dyn Fn(&TokenKind) -> Option<String>, out: &mut String) {
+    let wrap = |tag: &str, children: &[Node], out: &mut String| {
+        out.push_str(&format!("<{}>", tag));
+        html(children, resolve, out);
+        let name = tag.split(' ').next().unwrap_or(tag);
+        out.push_str(&format!("</{}>", name));
+    };
+
+    for node in nodes {
+        match node {
+            Node::Text(text) => {
+                for (i, line) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        out.push_str("<br>");
+                    }
+                    escape_html(line, out);
+                }
+            }
+            Node::Bold(children) => wrap("strong", children, out),
+            Node::Italic(children) => wrap("em", children, out),
+            Node::Underline(children) => wrap("u", children, out),
+            Node::Strikethrough(children) => wrap("s", children, out),
+            Node::Spoiler(children) => wrap("span class=\"spoiler\"", children, out),
+            Node::InlineCode(code) => {
+                out.push_str("<code>");
+                escape_html(code, out);
+                out.push_str("</code>");
+            }
+            Node::CodeBlock { language, code } => {
+                match language {
+                    Some(language) => {
+                        out.push_str("<pre><code class=\"language-");
+                        escape_html(language, out);
+                        out.push_str("\">");
+                    }
+                    None => out.push_str("<pre><code>"),
+                }
+                escape_html(code, out);
+                out.push_str("</code></pre>");
+            }
+            Node::BlockQuote(children) => wrap("blockquote", children, out),
+            Node::Heading { level, children } => wrap(&format!("h{}", level), children, out),
+            Node::List { start, items } => {
+                let tag = match start {
+                    Some(start) => format!("ol start=\"{}\"", start),
+                    None => "ul".to_string(),
+                };
+                out.push_str(&format!("<{}>", tag));
+                for item in items {
+                    wrap("li", item, out);
+                }
+                out.push_str(if start.is_some() { "</ol>" } else { "</ul>" });
+            }
+            Node::Link { url, children } => {
+                out.push_str("<a href=\"");
+                escape_html(url, out);
+                out.push_str("\" rel=\"noopener noreferrer nofollow\">");
+                html(children, resolve, out);
+                out.push_str("</a>");
+            }
+            Node::Url(url) => {
+                out.push_str("<a href=\"");
+                escape_html(url, out);
+                out.push_str("\" rel=\"noopener noreferrer nofollow\">");
+                escape_html(url, out);
+                out.push_str("</a>");
+            }
+            Node::Token(kind @ TokenKind::Emoji(emoji)) => {
+                let extension = if emoji.animated == Some(true) {
+                    "gif"
+                } else {
+                    "png"
+                };
+                let id = emoji.id.unwrap_or_default();
+                out.push_str(&format!(
+                    "<img class=\"emoji\" src=\"{}/emojis/{}.{}\" alt=\"",
+                    cdn::BASE,
+                    id,
+                    extension
+                ));
+                escape_html(&token_text(kind, resolve), out);
+                out.push_str("\">");
+            }
+            Node::Token(kind @ TokenKind::Timestamp { time, .. }) => {
+                out.push_str(&format!("<time datetime=\"{}\">", time.to_rfc3339()));
+                escape_html(&token_text(kind, resolve), out);
+                out.push_str("</time>");
+            }
+            Node::Token(kind) => {
+                out.push_str("<span class=\"mention\">");
+                escape_html(&token_text(kind, resolve), out);
+                out.push_str("</span>");
+            }
+        }
+    }
+}
+
+fn plain(nodes: &[Node], resolve: &dyn Fn(&TokenKind) -> Option<String>, out: &mut String) {
+    for (i, node) in nodes.iter().enumerate() {
+        // blocks sit on lines of their own
+        if is_block(node) && !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Bold(children)
+            | Node::Italic(children)
+            | Node::Underline(children)
+            | Node::Strikethrough(children)
+            | Node::Spoiler(children)
+            | Node::Heading { children, .. } => plain(children, resolve, out),
+            Node::InlineCode(code) | Node::CodeBlock { code, .. } => out.push_str(code),
+            Node::BlockQuote(children) => {
+                let quoted = to_plain_text(children, resolve);
+                let lines: Vec<String> = quoted.lines().map(|line| format!("> {}", line)).collect();
+                out.push_str(&lines.join("\n"));
+            }
+            Node::List { start, items } => {
+                for (n, item) in items.iter().enumerate() {
+                    if n > 0 {
+                        out.push('\n');
+                    }
+                    match start {
+                        Some(start) => out.push_str(&format!("{}. ", start + n as u64)),
+                        None => out.push_str("- "),
+                    }
+                    plain(item, resolve, out);
+                }
+            }
+            Node::Link { url, children } => {
+                plain(children, resolve, out);
+                out.push_str(&format!(" ({})", url));
+            }
+            Node::Url(url) => out.push_str(url),
+            Node::Token(kind) => out.push_str(&token_text(kind, resolve)),
+        }
+        if is_block(node) && i + 1 < nodes.len() {
+            out.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(content: &str) -> String {
+        to_html(&parse(content), |_| None)
+    }
+
+    #[test]
+    fn code_inside_formatting_cannot_close_it() {
+        assert_eq!(
+            render("||`a||`||"),
+            "<span class=\"spoiler\"><code>a||</code></span>"
+        );
+        assert_eq!(render("**`**` b**"), "<strong><code>**</code> b</strong>");
+        assert_eq!(render("~~``a~~``~~"), "<s><code>a~~</code></s>");
+        assert_eq!(render("*`*`*"), "<em><code>*</code></em>");
+        assert_eq!(render("_`_`_"), "<em><code>_</code></em>");
+        assert_eq!(
+            render("__```\na__\n```__"),
+            "<u><pre><code>a__</code></pre></u>"
+        );
+        assert_eq!(render("||``a||"), "<span class=\"spoiler\">``a</span>");
+    }
+
+    #[test]
+    fn formatting_nests() {
+        assert_eq!(
+            render("**bold *italic* __under ~~struck~~__**"),
+            "<strong>bold <em>italic</em> <u>under <s>struck</s></u></strong>"
+        );
+        assert_eq!(render("***both***"), "<strong><em>both</em></strong>");
+        assert_eq!(
+            render("||a **b**||"),
+            "<span class=\"spoiler\">a <strong>b</strong></span>"
+        );
+        assert_eq!(render("snake_case_name"), "snake_case_name");
+        assert_eq!(render("a * b * c"), "a * b * c");
+    }
+
+    #[test]
+    fn escapes_are_plain_text() {
+        assert_eq!(render("\\*not italic\\*"), "*not italic*");
+        assert_eq!(render("\\**not bold**"), "**not bold**");
+        assert_eq!(render("\\`code\\`"), "`code`");
+        assert_eq!(render("a\\b"), "a\\b");
+    }
+
+    #[test]
+    fn code_comes_before_everything_else() {
+        assert_eq!(render("`**a**`"), "<code>**a**</code>");
+        assert_eq!(render("`\\`"), "<code>\\</code>");
+        assert_eq!(render("`` a`b ``"), "<code>a`b</code>");
+        assert_eq!(
+            render("```rust\nlet a = *b*;\n```\nafter"),
+            "<pre><code class=\"language-rust\">let a = *b*;</code></pre>after"
+        );
+        assert_eq!(
+            render("```\n> not a quote\n# nor a heading\n```"),
+            "<pre><code>&gt; not a quote\n# nor a heading</code></pre>"
+        );
+        assert_eq!(render("`unclosed"), "`unclosed");
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(
+            render("> one\n> **two**\nafter"),
+            "<blockquote>one<br><strong>two</strong></blockquote>after"
+        );
+        assert_eq!(
+            render("before\n>>> all\nof this"),
+            "before<blockquote>all<br>of this</blockquote>"
+        );
+        assert_eq!(render("> > nested"), "<blockquote>&gt; nested</blockquote>");
+        assert_eq!(render(">no space"), "&gt;no space");
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(
+            render("- one\n* *two*\nafter"),
+            "<ul><li>one</li><li><em>two</em></li></ul>after"
+        );
+        assert_eq!(
+            render("3. three\n4. four"),
+            "<ol start=\"3\"><li>three</li><li>four</li></ol>"
+        );
+        assert_eq!(
+            render("- one\n1. two"),
+            "<ul><li>one</li></ul><ol start=\"1\"><li>two</li></ol>"
+        );
+        assert_eq!(render("-no space"), "-no space");
+    }
+
+    #[test]
+    fn headings() {
+        assert_eq!(render("# one"), "<h1>one</h1>");
+        assert_eq!(render("### *three*\ntext"), "<h3><em>three</em></h3>text");
+        assert_eq!(render("#### four"), "#### four");
+        assert_eq!(render("#no space"), "#no space");
+        assert_eq!(render("text\n## two"), "text<h2>two</h2>");
+    }
+
+    #[test]
+    fn masked_link_urls_keep_balanced_parentheses() {
+        assert_eq!(
+            render("[x](https://en.wikipedia.org/wiki/Foo_(bar))"),
+            "<a href=\"https://en.wikipedia.org/wiki/Foo_(bar)\" \
+             rel=\"noopener noreferrer nofollow\">x</a>"
+        );
+        assert_eq!(
+            render("([x](https://a.com/(b)(c)))"),
+            "(<a href=\"https://a.com/(b)(c)\" \
+             rel=\"noopener noreferrer nofollow\">x</a>)"
+        );
+        assert_eq!(
+            render("[x](<https://a.com/(b)>) after"),
+            "<a href=\"https://a.com/(b)\" \
+             rel=\"noopener noreferrer nofollow\">x</a> after"
+        );
+        // a ( that is never closed leaves the link unclosed too, so only the bare
+        // url is linked
+        assert_eq!(
+            render("[x](https://a.com/(b)"),
+            "[x](<a href=\"https://a.com/(b)\" \
+             rel=\"noopener noreferrer nofollow\">https://a.com/(b)</a>"
+        );
+    }
+
+    #[test]
+    fn links_and_text_are_escaped() {
+        assert_eq!(
+            render("[<b>](https://a.com/?x=\"y\"&z='w')"),
+            "<a href=\"https://a.com/?x=&quot;y&quot;&amp;z=&#39;w&#39;\" \
+             rel=\"noopener noreferrer nofollow\">&lt;b&gt;</a>"
+        );
+        assert_eq!(
+            render("see https://a.com/a_b_c."),
+            "see <a href=\"https://a.com/a_b_c\" \
+             rel=\"noopener noreferrer nofollow\">https://a.com/a_b_c</a>."
+        );
+        assert_eq!(
+            render("[x](javascript:alert(1))"),
+            "[x](javascript:alert(1))"
+        );
+        assert_eq!(render("<script>&"), "&lt;script&gt;&amp;");
+        assert_eq!(
+            to_plain_text(&parse("**a** [b](https://c.com) `d`"), |_| None),
+            "a b (https://c.com) d"
+        );
+    }
+}
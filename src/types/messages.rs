@@ -1,53 +1,1035 @@
-use crate::types::snowflakes::Snowflake;
-use crate::types::users::User;
+use serde::{Deserialize, Serialize};
 
-pub use stickers::Sticker;
-pub use emojis::Emoji;
+use crate::types::channels::{Channel, ChannelType};
+use crate::types::snowflakes::{self, Snowflake};
+use crate::types::users::{Member, User};
+use crate::types::DiscordTimestamp;
+
+pub use components::Component;
+pub use embeds::Embed;
+pub use emojis::{Emoji, ReactionType};
+pub use stickers::{Sticker, StickerItem};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Message {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // id of the message
+    #[serde(with = "snowflakes::string")]
+    pub channel_id: Snowflake, // id of the channel the message was sent in
+    #[serde(default, with = "snowflakes::option")]
+    pub guild_id: Option<Snowflake>, // * id of the guild the message was sent in
+    pub author: User, // the author of this message (not guaranteed to be a valid user if sent by a webhook)
+    pub member: Option<Member>, // * member properties for this message's author
+    pub content: String, // contents of the message
+    pub timestamp: DiscordTimestamp, // when this message was sent
+    pub edited_timestamp: Option<DiscordTimestamp>, // when this message was edited (or null if never)
+    pub tts: bool,                                  // whether this was a TTS message
+    pub mention_everyone: bool,                     // whether this message mentions everyone
+    #[serde(default)]
+    pub mentions: Vec<User>,   // users specifically mentioned in the message
+    #[serde(default, with = "snowflakes::vec")]
+    pub mention_roles: Vec<Snowflake>, // roles specifically mentioned in this message
+    #[serde(default)]
+    pub mention_channels: Vec<ChannelMention>, // channels specifically mentioned in this message
+    #[serde(default)]
+    pub attachments: Vec<Attachment>, // any attached files
+    #[serde(default)]
+    pub embeds: Vec<Embed>,    // any embedded content
+    #[serde(default)]
+    pub reactions: Vec<Reaction>, // reactions to the message
+    pub nonce: Option<serde_json::Value>,           // used for validating a message was sent
+    pub pinned: bool,                               // whether this message is pinned
+    #[serde(default, with = "snowflakes::option")]
+    pub webhook_id: Option<Snowflake>, // if the message is generated by a webhook, this is the webhook's id
+    #[serde(rename = "type")]
+    pub m_type: MessageType, // type of message
+    pub activity: Option<MessageActivity>, // sent with Rich Presence-related chat embeds
+    #[serde(default, with = "snowflakes::option")]
+    pub application_id: Option<Snowflake>, // if the message is an Interaction or application-owned webhook, this is the id of the application
+    pub message_reference: Option<MessageReference>, // data showing the source of a crosspost, channel follow add, pin, or reply message
+    #[serde(default)]
+    pub flags: MessageFlags,    // message flags combined as a bitfield
+    pub referenced_message: Option<Box<Message>>, // the message associated with the message_reference
+    pub interaction: Option<MessageInteraction>, // sent if the message is a response to an Interaction
+    pub thread: Option<Channel>, // the thread that was started from this message, includes thread member object
+    #[serde(default)]
+    pub components: Vec<Component>, // sent if the message contains components like buttons, action rows, or other interactive components
+    #[serde(default)]
+    pub sticker_items: Vec<StickerItem>, // sent if the message contains stickers
+    pub position: Option<i32>, // the approximate position of the message in a thread
+}
+
+int_enum!(MessageType: u8 {
+    Default = 0,
+    RecipientAdd = 1,
+    RecipientRemove = 2,
+    Call = 3,
+    ChannelNameChange = 4,
+    ChannelIconChange = 5,
+    ChannelPinnedMessage = 6,
+    UserJoin = 7,
+    GuildBoost = 8,
+    GuildBoostTier1 = 9,
+    GuildBoostTier2 = 10,
+    GuildBoostTier3 = 11,
+    ChannelFollowAdd = 12,
+    GuildDiscoveryDisqualified = 14,
+    GuildDiscoveryRequalified = 15,
+    GuildDiscoveryGracePeriodInitialWarning = 16,
+    GuildDiscoveryGracePeriodFinalWarning = 17,
+    ThreadCreated = 18,
+    Reply = 19,
+    ChatInputCommand = 20,
+    ThreadStarterMessage = 21,
+    GuildInviteReminder = 22,
+    ContextMenuCommand = 23,
+    AutoModerationAction = 24,
+    RoleSubscriptionPurchase = 25,
+    InteractionPremiumUpsell = 26,
+    StageStart = 27,
+    StageEnd = 28,
+    StageSpeaker = 29,
+    StageTopic = 31,
+    GuildApplicationPremiumSubscription = 32,
+});
+
+flags!(MessageFlags: u64 {
+    CROSSPOSTED = 0, // this message has been published to subscribed channels (via Channel Following)
+    IS_CROSSPOST = 1, // this message originated from a message in another channel (via Channel Following)
+    SUPPRESS_EMBEDS = 2, // do not include any embeds when serializing this message
+    SOURCE_MESSAGE_DELETED = 3, // the source message for this crosspost has been deleted (via Channel Following)
+    URGENT = 4, // this message came from the urgent message system
+    HAS_THREAD = 5, // this message has an associated thread, with the same id as the message
+    EPHEMERAL = 6, // this message is only visible to the user who invoked the Interaction
+    LOADING = 7, // this message is an Interaction Response and the bot is "thinking"
+    FAILED_TO_MENTION_SOME_ROLES_IN_THREAD = 8, // this message failed to mention some roles and add their members to the thread
+    SUPPRESS_NOTIFICATIONS = 12, // this message will not trigger push and desktop notifications
+    IS_VOICE_MESSAGE = 13, // this message is a voice message
+});
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ChannelMention {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // id of the channel
+    #[serde(with = "snowflakes::string")]
+    pub guild_id: Snowflake, // id of the guild containing the channel
+    #[serde(rename = "type")]
+    pub channel_type: ChannelType, // the type of channel
+    pub name: String, // the name of the channel
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Attachment {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // attachment id
+    pub filename: String,             // name of file attached
+    pub description: Option<String>,  // description for the file (max 1024 characters)
+    pub content_type: Option<String>, // the attachment's media type
+    pub size: u64,                    // size of file in bytes
+    pub url: String,                  // source url of file
+    pub proxy_url: String,            // a proxied url of file
+    pub height: Option<u32>,          // height of file (if image)
+    pub width: Option<u32>,           // width of file (if image)
+    pub ephemeral: Option<bool>,      // whether this attachment is ephemeral
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Reaction {
+    pub count: i32,   // times this emoji has been used to react
+    pub me: bool,     // whether the current user reacted using this emoji
+    pub emoji: Emoji, // emoji information
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
+pub struct MessageReference {
+    #[serde(
+        default,
+        with = "snowflakes::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub message_id: Option<Snowflake>, // id of the originating message
+    #[serde(
+        default,
+        with = "snowflakes::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub channel_id: Option<Snowflake>, // * id of the originating message's channel
+    #[serde(
+        default,
+        with = "snowflakes::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub guild_id: Option<Snowflake>, // id of the originating message's guild
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_if_not_exists: Option<bool>, // when sending, whether to error if the referenced message doesn't exist instead of sending as a normal (non-reply) message, default true
+}
+
+str_enum!(AllowedMentionType {
+    Roles = "roles",       // controls role mentions
+    Users = "users",       // controls user mentions
+    Everyone = "everyone", // controls @everyone and @here mentions
+});
+
+// which mentions in a message may ping; the default lets users be pinged but
+// never @everyone, @here or roles, whatever the content says
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct AllowedMentions {
+    #[serde(default)]
+    pub parse: Vec<AllowedMentionType>, // mention types to parse from the content
+    #[serde(default, with = "snowflakes::vec")]
+    pub roles: Vec<Snowflake>, // roles that may be mentioned (max 100), only without "roles" in parse
+    #[serde(default, with = "snowflakes::vec")]
+    pub users: Vec<Snowflake>, // users that may be mentioned (max 100), only without "users" in parse
+    #[serde(default)]
+    pub replied_user: bool, // for replies, whether to mention the author of the message being replied to
+}
+
+impl Default for AllowedMentions {
+    fn default() -> Self {
+        AllowedMentions {
+            parse: vec![AllowedMentionType::Users],
+            roles: Vec::new(),
+            users: Vec::new(),
+            replied_user: true,
+        }
+    }
+}
+
+impl AllowedMentions {
+    // nothing pings, not even the replied user
+    pub fn none() -> Self {
+        AllowedMentions {
+            parse: Vec::new(),
+            roles: Vec::new(),
+            users: Vec::new(),
+            replied_user: false,
+        }
+    }
+
+    // everything in the content pings, including @everyone
+    pub fn all() -> Self {
+        AllowedMentions {
+            parse: vec![
+                AllowedMentionType::Roles,
+                AllowedMentionType::Users,
+                AllowedMentionType::Everyone,
+            ],
+            ..AllowedMentions::default()
+        }
+    }
+
+    // only these users may be pinged; discord rejects a users list alongside parsed users
+    pub fn users(mut self, ids: impl IntoIterator<Item = Snowflake>) -> Self {
+        self.parse.retain(|t| *t != AllowedMentionType::Users);
+        self.users = ids.into_iter().collect();
+        self
+    }
+
+    // only these roles may be pinged
+    pub fn roles(mut self, ids: impl IntoIterator<Item = Snowflake>) -> Self {
+        self.parse.retain(|t| *t != AllowedMentionType::Roles);
+        self.roles = ids.into_iter().collect();
+        self
+    }
+
+    pub fn everyone(mut self, allow: bool) -> Self {
+        self.parse.retain(|t| *t != AllowedMentionType::Everyone);
+        if allow {
+            self.parse.push(AllowedMentionType::Everyone);
+        }
+        self
+    }
+
+    pub fn replied_user(mut self, mention: bool) -> Self {
+        self.replied_user = mention;
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MessageActivity {
+    #[serde(rename = "type")]
+    pub activity_type: i32, // type of message activity
+    pub party_id: Option<String>, // party_id from a Rich Presence event
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MessageInteraction {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // id of the interaction
+    #[serde(rename = "type")]
+    pub interaction_type: i32, // the type of interaction
+    pub name: String, // the name of the application command, including subcommands and subcommand groups
+    pub user: User,   // the user who invoked the interaction
+    pub member: Option<Member>, // the member who invoked the interaction in the guild
+}
 
 pub mod stickers {
-    use super::Snowflake;
     use super::User;
+    use super::{snowflakes, Snowflake};
+    use super::{Deserialize, Serialize};
 
+    #[derive(Deserialize, Serialize, Clone, Debug)]
     pub struct Sticker {
-        id: Snowflake,
-        pack_id: Option<Snowflake>,
-        name: &'static str,
-        description: Option<&'static str>,
-        tags: Option<&'static str>,
-        m_type: Option<StickerType>,
-        format_type: StickerFormat, 
-        available: Option<bool>,
-        guild_id: Option<Snowflake>,
-        user: Option<User>,
-        sort_value: Option<i32>,
+        #[serde(with = "snowflakes::string")]
+        pub id: Snowflake,
+        #[serde(default, with = "snowflakes::option")]
+        pub pack_id: Option<Snowflake>,
+        pub name: String,
+        pub description: Option<String>,
+        pub tags: Option<String>,
+        #[serde(rename = "type")]
+        pub m_type: Option<StickerType>,
+        pub format_type: StickerFormat,
+        pub available: Option<bool>,
+        #[serde(default, with = "snowflakes::option")]
+        pub guild_id: Option<Snowflake>,
+        pub user: Option<User>,
+        pub sort_value: Option<i32>,
     }
 
-    enum StickerType {
-        STANDARD = 1,
-        GUILD = 2,
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct StickerItem {
+        #[serde(with = "snowflakes::string")]
+        pub id: Snowflake,
+        pub name: String,
+        pub format_type: StickerFormat,
     }
 
-    pub enum StickerFormat {
-        PNG = 1,
-        APNG = 2,
-        LOTTIE = 3,
-    }
+    int_enum!(StickerType: u8 {
+        Standard = 1,
+        Guild = 2,
+    });
+
+    int_enum!(StickerFormat: u8 {
+        Png = 1,
+        Apng = 2,
+        Lottie = 3,
+        Gif = 4,
+    });
 
+    #[derive(Deserialize, Serialize, Clone, Debug)]
     pub struct StickerPack {
-        id: Snowflake,
-        name: &'static str,
-        sku_id: Snowflake,
-        cover_sticker_id: Option<Snowflake>,
-        description: &'static str,
-        stickers: [Sticker],
+        #[serde(with = "snowflakes::string")]
+        pub id: Snowflake,
+        pub name: String,
+        #[serde(with = "snowflakes::string")]
+        pub sku_id: Snowflake,
+        #[serde(default, with = "snowflakes::option")]
+        pub cover_sticker_id: Option<Snowflake>,
+        pub description: String,
+        pub stickers: Vec<Sticker>,
     }
 }
 
 pub mod emojis {
-    use super::Snowflake;
+    use super::User;
+    use super::{snowflakes, Snowflake};
+    use super::{Deserialize, Serialize};
 
+    #[derive(Deserialize, Serialize, Clone, Debug)]
     pub struct Emoji {
-        id: Snowflake,
+        #[serde(default, with = "snowflakes::option")]
+        pub id: Option<Snowflake>, // emoji id, null for unicode emojis
+        pub name: Option<String>, // emoji name (can be null only in reaction emoji objects)
+        #[serde(default, with = "snowflakes::vec")]
+        pub roles: Vec<Snowflake>, // roles allowed to use this emoji
+        pub user: Option<User>,   // user that created this emoji
+        pub require_colons: Option<bool>, // whether this emoji must be wrapped in colons
+        pub managed: Option<bool>, // whether this emoji is managed
+        pub animated: Option<bool>, // whether this emoji is animated
+        pub available: Option<bool>, // whether this emoji can be used, may be false due to loss of Server Boosts
+    }
+
+    // an emoji as used in reactions: either a unicode emoji or a custom one
+    // identified by its id
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    pub enum ReactionType {
+        Unicode(String),
+        Custom {
+            id: Snowflake,
+            name: Option<String>,
+            animated: bool,
+        },
+    }
+
+    impl ReactionType {
+        // accepts a unicode emoji, a <:name:id> or <a:name:id> mention, or name:id
+        pub fn parse(s: &str) -> Option<Self> {
+            let s = s.trim();
+            if s.is_empty() {
+                return None;
+            }
+            let (animated, inner) = match s.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+                Some(inner) => match inner.strip_prefix("a:") {
+                    Some(rest) => (true, rest),
+                    None => (false, inner.strip_prefix(':')?),
+                },
+                None => (false, s),
+            };
+            match inner.rsplit_once(':') {
+                // ids are plain digits, which parse() alone would not ensure
+                Some((_, id)) if !id.bytes().all(|b| b.is_ascii_digit()) => None,
+                Some((name, id)) => Some(ReactionType::Custom {
+                    id: id.parse().ok()?,
+                    name: (!name.is_empty()).then(|| name.to_string()),
+                    animated,
+                }),
+                None if inner.len() == s.len() => Some(ReactionType::Unicode(s.to_string())),
+                None => None,
+            }
+        }
+    }
+
+    impl From<&Emoji> for ReactionType {
+        fn from(emoji: &Emoji) -> Self {
+            match emoji.id {
+                Some(id) => ReactionType::Custom {
+                    id,
+                    name: emoji.name.clone(),
+                    animated: emoji.animated.unwrap_or(false),
+                },
+                None => ReactionType::Unicode(emoji.name.clone().unwrap_or_default()),
+            }
+        }
+    }
+
+    impl From<&str> for ReactionType {
+        fn from(emoji: &str) -> Self {
+            ReactionType::Unicode(emoji.to_string())
+        }
+    }
+
+    impl From<char> for ReactionType {
+        fn from(emoji: char) -> Self {
+            ReactionType::Unicode(emoji.to_string())
+        }
+    }
+
+    // formats the emoji the way it is written in message content
+    impl std::fmt::Display for ReactionType {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self {
+                ReactionType::Unicode(emoji) => f.write_str(emoji),
+                ReactionType::Custom { id, name, animated } => {
+                    let prefix = if *animated { "a" } else { "" };
+                    write!(f, "<{}:{}:{}>", prefix, name.as_deref().unwrap_or("_"), id)
+                }
+            }
+        }
+    }
+}
+
+pub mod embeds {
+    use std::fmt;
+
+    use super::DiscordTimestamp;
+    use super::{Deserialize, Serialize};
+
+    pub const MAX_EMBEDS: usize = 10; // embeds per message
+    pub const MAX_TITLE: usize = 256;
+    pub const MAX_DESCRIPTION: usize = 4096;
+    pub const MAX_FIELDS: usize = 25;
+    pub const MAX_FIELD_NAME: usize = 256;
+    pub const MAX_FIELD_VALUE: usize = 1024;
+    pub const MAX_FOOTER_TEXT: usize = 2048;
+    pub const MAX_AUTHOR_NAME: usize = 256;
+    pub const MAX_TOTAL: usize = 6000; // characters across all embeds of a message
+
+    #[derive(Deserialize, Serialize, Clone, Default, Debug)]
+    pub struct Embed {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub title: Option<String>, // title of embed
+        #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+        pub embed_type: Option<String>, // type of embed (always "rich" for webhook embeds)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>, // description of embed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub url: Option<String>, // url of embed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub timestamp: Option<DiscordTimestamp>, // timestamp of embed content
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub color: Option<u32>, // color code of the embed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub footer: Option<EmbedFooter>, // footer information
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub image: Option<EmbedImage>, // image information
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub thumbnail: Option<EmbedImage>, // thumbnail information
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub video: Option<EmbedVideo>, // video information
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub provider: Option<EmbedProvider>, // provider information
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub author: Option<EmbedAuthor>, // author information
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub fields: Vec<EmbedField>, // fields information
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EmbedFooter {
+        pub text: String, // footer text
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub icon_url: Option<String>, // url of footer icon (only supports http(s) and attachments)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub proxy_icon_url: Option<String>, // a proxied url of footer icon
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EmbedImage {
+        pub url: String, // source url of image (only supports http(s) and attachments)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub proxy_url: Option<String>, // a proxied url of the image
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub height: Option<u32>, // height of image
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub width: Option<u32>, // width of image
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EmbedVideo {
+        pub url: Option<String>,       // source url of video
+        pub proxy_url: Option<String>, // a proxied url of the video
+        pub height: Option<u32>,       // height of video
+        pub width: Option<u32>,        // width of video
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EmbedProvider {
+        pub name: Option<String>, // name of provider
+        pub url: Option<String>,  // url of provider
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EmbedAuthor {
+        pub name: String, // name of author
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub url: Option<String>, // url of author (only supports http(s))
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub icon_url: Option<String>, // url of author icon (only supports http(s) and attachments)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub proxy_icon_url: Option<String>, // a proxied url of author icon
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EmbedField {
+        pub name: String,  // name of the field
+        pub value: String, // value of the field
+        #[serde(default)]
+        pub inline: bool, // whether or not this field should display inline
+    }
+
+    impl Embed {
+        pub fn new() -> Self {
+            Embed::default()
+        }
+
+        pub fn title(mut self, title: impl Into<String>) -> Self {
+            self.title = Some(title.into());
+            self
+        }
+
+        pub fn description(mut self, description: impl Into<String>) -> Self {
+            self.description = Some(description.into());
+            self
+        }
+
+        pub fn url(mut self, url: impl Into<String>) -> Self {
+            self.url = Some(url.into());
+            self
+        }
+
+        pub fn timestamp(mut self, timestamp: DiscordTimestamp) -> Self {
+            self.timestamp = Some(timestamp);
+            self
+        }
+
+        // 0xRRGGBB
+        pub fn color(mut self, color: u32) -> Self {
+            self.color = Some(color);
+            self
+        }
+
+        pub fn footer(mut self, footer: EmbedFooter) -> Self {
+            self.footer = Some(footer);
+            self
+        }
+
+        pub fn image(mut self, url: impl Into<String>) -> Self {
+            self.image = Some(EmbedImage::new(url));
+            self
+        }
+
+        pub fn thumbnail(mut self, url: impl Into<String>) -> Self {
+            self.thumbnail = Some(EmbedImage::new(url));
+            self
+        }
+
+        pub fn author(mut self, author: EmbedAuthor) -> Self {
+            self.author = Some(author);
+            self
+        }
+
+        pub fn field(
+            mut self,
+            name: impl Into<String>,
+            value: impl Into<String>,
+            inline: bool,
+        ) -> Self {
+            self.fields.push(EmbedField {
+                name: name.into(),
+                value: value.into(),
+                inline,
+            });
+            self
+        }
+
+        // the characters discord counts towards the 6000 total
+        pub fn len(&self) -> usize {
+            let text = [
+                self.title.as_deref(),
+                self.description.as_deref(),
+                self.footer.as_ref().map(|f| f.text.as_str()),
+                self.author.as_ref().map(|a| a.name.as_str()),
+            ];
+            let fields = self.fields.iter().map(|f| chars(&f.name) + chars(&f.value));
+            text.iter().flatten().map(|t| chars(t)).sum::<usize>() + fields.sum::<usize>()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        // checks every limit that applies to a single embed
+        pub fn validate(&self) -> Result<(), EmbedError> {
+            check(EmbedPart::Title, self.title.as_deref(), MAX_TITLE)?;
+            check(
+                EmbedPart::Description,
+                self.description.as_deref(),
+                MAX_DESCRIPTION,
+            )?;
+            check(
+                EmbedPart::FooterText,
+                self.footer.as_ref().map(|f| f.text.as_str()),
+                MAX_FOOTER_TEXT,
+            )?;
+            check(
+                EmbedPart::AuthorName,
+                self.author.as_ref().map(|a| a.name.as_str()),
+                MAX_AUTHOR_NAME,
+            )?;
+            if self.fields.len() > MAX_FIELDS {
+                return Err(EmbedError::new(
+                    EmbedPart::Fields,
+                    self.fields.len(),
+                    MAX_FIELDS,
+                ));
+            }
+            for (i, field) in self.fields.iter().enumerate() {
+                check(EmbedPart::FieldName(i), Some(&field.name), MAX_FIELD_NAME)?;
+                check(
+                    EmbedPart::FieldValue(i),
+                    Some(&field.value),
+                    MAX_FIELD_VALUE,
+                )?;
+            }
+            if self.len() > MAX_TOTAL {
+                return Err(EmbedError::new(EmbedPart::Total, self.len(), MAX_TOTAL));
+            }
+            Ok(())
+        }
+    }
+
+    // checks the embeds of one message, including the limits they share
+    pub fn validate(embeds: &[Embed]) -> Result<(), EmbedError> {
+        if embeds.len() > MAX_EMBEDS {
+            return Err(EmbedError::new(EmbedPart::Embeds, embeds.len(), MAX_EMBEDS));
+        }
+        for (i, embed) in embeds.iter().enumerate() {
+            embed.validate().map_err(|e| EmbedError { index: i, ..e })?;
+        }
+        let total = embeds.iter().map(Embed::len).sum();
+        if total > MAX_TOTAL {
+            return Err(EmbedError::new(EmbedPart::Total, total, MAX_TOTAL));
+        }
+        Ok(())
+    }
+
+    fn chars(s: &str) -> usize {
+        s.chars().count()
+    }
+
+    fn check(part: EmbedPart, text: Option<&str>, limit: usize) -> Result<(), EmbedError> {
+        match text.map(chars) {
+            Some(len) if len > limit => Err(EmbedError::new(part, len, limit)),
+            _ => Ok(()),
+        }
+    }
+
+    // the part of an embed that broke a limit
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum EmbedPart {
+        Embeds, // the number of embeds in the message
+        Title,
+        Description,
+        Fields, // the number of fields
+        FieldName(usize),
+        FieldValue(usize),
+        FooterText,
+        AuthorName,
+        Total, // the characters in the embed, or in all of the message's embeds
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub struct EmbedError {
+        pub index: usize,    // which embed of the message, 0 for message-wide limits
+        pub part: EmbedPart, // what was too long
+        pub len: usize,      // its length, in characters or items
+        pub limit: usize,    // the most discord allows
+    }
+
+    impl EmbedError {
+        fn new(part: EmbedPart, len: usize, limit: usize) -> Self {
+            EmbedError {
+                index: 0,
+                part,
+                len,
+                limit,
+            }
+        }
+    }
+
+    impl fmt::Display for EmbedError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let part = match self.part {
+                EmbedPart::Embeds => {
+                    return write!(f, "{} embeds is over the limit of {}", self.len, self.limit)
+                }
+                EmbedPart::Title => "title".to_string(),
+                EmbedPart::Description => "description".to_string(),
+                EmbedPart::Fields => {
+                    return write!(
+                        f,
+                        "embed {} has {} fields, over the limit of {}",
+                        self.index, self.len, self.limit
+                    )
+                }
+                EmbedPart::FieldName(i) => format!("fields[{}].name", i),
+                EmbedPart::FieldValue(i) => format!("fields[{}].value", i),
+                EmbedPart::FooterText => "footer.text".to_string(),
+                EmbedPart::AuthorName => "author.name".to_string(),
+                EmbedPart::Total => {
+                    return write!(
+                        f,
+                        "embeds have {} characters in total, over the limit of {}",
+                        self.len, self.limit
+                    )
+                }
+            };
+            write!(
+                f,
+                "embed {} {} has {} characters, over the limit of {}",
+                self.index, part, self.len, self.limit
+            )
+        }
     }
 
+    impl std::error::Error for EmbedError {}
+
+    impl EmbedFooter {
+        pub fn new(text: impl Into<String>) -> Self {
+            EmbedFooter {
+                text: text.into(),
+                icon_url: None,
+                proxy_icon_url: None,
+            }
+        }
+
+        pub fn icon_url(mut self, url: impl Into<String>) -> Self {
+            self.icon_url = Some(url.into());
+            self
+        }
+    }
+
+    impl EmbedImage {
+        pub fn new(url: impl Into<String>) -> Self {
+            EmbedImage {
+                url: url.into(),
+                proxy_url: None,
+                height: None,
+                width: None,
+            }
+        }
+    }
+
+    impl EmbedAuthor {
+        pub fn new(name: impl Into<String>) -> Self {
+            EmbedAuthor {
+                name: name.into(),
+                url: None,
+                icon_url: None,
+                proxy_icon_url: None,
+            }
+        }
+
+        pub fn url(mut self, url: impl Into<String>) -> Self {
+            self.url = Some(url.into());
+            self
+        }
+
+        pub fn icon_url(mut self, url: impl Into<String>) -> Self {
+            self.icon_url = Some(url.into());
+            self
+        }
+    }
+}
+
+pub mod components {
+    use super::{ChannelType, Emoji};
+    use super::{Deserialize, Serialize};
+
+    // a single struct covers every component type; fields not used by a type are left unset
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct Component {
+        #[serde(rename = "type")]
+        pub component_type: ComponentType, // type of component
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub custom_id: Option<String>, // developer-defined identifier, max 100 characters
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub disabled: Option<bool>, // whether the component is disabled
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub style: Option<u8>, // button or text input style
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub label: Option<String>, // text that appears on the button or above the text input
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub emoji: Option<Emoji>, // name, id, and animated of the button emoji
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub url: Option<String>, // url for link-style buttons
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub options: Option<Vec<SelectOption>>, // specified choices in a string select menu, max 25
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub channel_types: Option<Vec<ChannelType>>, // list of channel types to include in a channel select menu
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub placeholder: Option<String>, // placeholder text if nothing is selected, max 150 characters
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub min_values: Option<u8>, // minimum number of items that must be chosen
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub max_values: Option<u8>, // maximum number of items that can be chosen
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub min_length: Option<u16>, // minimum input length for a text input
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub max_length: Option<u16>, // maximum input length for a text input
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub required: Option<bool>, // whether this text input is required to be filled
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub value: Option<String>, // pre-filled value for a text input
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub components: Vec<Component>, // child components of an action row
+    }
+
+    int_enum!(ComponentType: u8 {
+        ActionRow = 1,         // container for other components
+        Button = 2,            // button object
+        StringSelect = 3,      // select menu for picking from defined text options
+        TextInput = 4,         // text input object
+        UserSelect = 5,        // select menu for users
+        RoleSelect = 6,        // select menu for roles
+        MentionableSelect = 7, // select menu for mentionables (users and roles)
+        ChannelSelect = 8,     // select menu for channels
+    });
+
+    int_enum!(ButtonStyle: u8 {
+        Primary = 1,
+        Secondary = 2,
+        Success = 3,
+        Danger = 4,
+        Link = 5,
+    });
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct SelectOption {
+        pub label: String, // user-facing name of the option, max 100 characters
+        pub value: String, // dev-defined value of the option, max 100 characters
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>, // additional description of the option, max 100 characters
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub emoji: Option<Emoji>, // id, name, and animated
+        #[serde(default)]
+        pub default: bool, // will show this option as selected by default
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::embeds::{self, *};
+    use super::ReactionType;
+
+    // limits count characters, not bytes
+    fn text(len: usize) -> String {
+        "é".repeat(len)
+    }
+
+    type Build = fn(String) -> Embed; // puts the text into the part under test
+
+    fn broken(embed: &Embed) -> Option<(EmbedPart, usize, usize)> {
+        embed.validate().err().map(|e| (e.part, e.len, e.limit))
+    }
+
+    #[test]
+    fn each_text_limit_allows_its_length_and_no_more() {
+        let limits: [(Build, EmbedPart, usize); 6] = [
+            (|t| Embed::new().title(t), EmbedPart::Title, 256),
+            (
+                |t| Embed::new().description(t),
+                EmbedPart::Description,
+                4096,
+            ),
+            (
+                |t| Embed::new().field(t, "v", false),
+                EmbedPart::FieldName(0),
+                256,
+            ),
+            (
+                |t| Embed::new().field("n", t, false),
+                EmbedPart::FieldValue(0),
+                1024,
+            ),
+            (
+                |t| Embed::new().footer(EmbedFooter::new(t)),
+                EmbedPart::FooterText,
+                2048,
+            ),
+            (
+                |t| Embed::new().author(EmbedAuthor::new(t)),
+                EmbedPart::AuthorName,
+                256,
+            ),
+        ];
+        for (embed, part, limit) in limits {
+            assert_eq!(broken(&embed(text(limit))), None, "{:?}", part);
+            assert_eq!(
+                broken(&embed(text(limit + 1))),
+                Some((part, limit + 1, limit))
+            );
+        }
+    }
+
+    #[test]
+    fn fields_are_limited_in_number_and_reported_by_index() {
+        let mut embed = Embed::new();
+        for _ in 0..MAX_FIELDS {
+            embed = embed.field("n", "v", true);
+        }
+        assert_eq!(embed.fields.len(), 25);
+        assert_eq!(broken(&embed), None);
+        let embed = embed.field("n", "v", true);
+        assert_eq!(broken(&embed), Some((EmbedPart::Fields, 26, 25)));
+
+        let embed = Embed::new()
+            .field("n", "v", false)
+            .field(text(257), "v", false);
+        assert_eq!(broken(&embed), Some((EmbedPart::FieldName(1), 257, 256)));
+    }
+
+    #[test]
+    fn one_embed_holds_at_most_6000_characters() {
+        let full = Embed::new()
+            .description(text(4096))
+            .title(text(256))
+            .footer(EmbedFooter::new(text(1648)));
+        assert_eq!(full.len(), 6000);
+        assert_eq!(broken(&full), None);
+
+        let over = full.author(EmbedAuthor::new("x"));
+        assert_eq!(broken(&over), Some((EmbedPart::Total, 6001, 6000)));
+    }
+
+    #[test]
+    fn a_message_holds_at_most_10_embeds_and_6000_characters() {
+        let ten = vec![Embed::new().title("t"); 10];
+        assert_eq!(embeds::validate(&ten), Ok(()));
+        let eleven = vec![Embed::new().title("t"); 11];
+        let err = embeds::validate(&eleven).unwrap_err();
+        assert_eq!(
+            (err.index, err.part, err.len, err.limit),
+            (0, EmbedPart::Embeds, 11, 10)
+        );
+
+        let half = Embed::new().description(text(3000));
+        assert_eq!(embeds::validate(&[half.clone(), half.clone()]), Ok(()));
+        let err = embeds::validate(&[half.clone(), half.title("x")]).unwrap_err();
+        assert_eq!(
+            (err.index, err.part, err.len, err.limit),
+            (0, EmbedPart::Total, 6001, 6000)
+        );
+
+        // limits of a single embed name the embed that broke them
+        let err = embeds::validate(&[Embed::new(), Embed::new().title(text(257))]).unwrap_err();
+        assert_eq!((err.index, err.part), (1, EmbedPart::Title));
+    }
+
+    fn custom(id: i64, name: &str, animated: bool) -> Option<ReactionType> {
+        Some(ReactionType::Custom {
+            id,
+            name: Some(name.to_string()),
+            animated,
+        })
+    }
+
+    #[test]
+    fn parses_unicode_and_custom_emojis() {
+        assert_eq!(
+            ReactionType::parse("👍"),
+            Some(ReactionType::Unicode("👍".to_string()))
+        );
+        assert_eq!(
+            ReactionType::parse(" 👨‍👩‍👧 "),
+            Some(ReactionType::Unicode("👨‍👩‍👧".to_string()))
+        );
+        assert_eq!(
+            ReactionType::parse("<:blob:123456>"),
+            custom(123456, "blob", false)
+        );
+        assert_eq!(
+            ReactionType::parse("<a:blob:123456>"),
+            custom(123456, "blob", true)
+        );
+        assert_eq!(
+            ReactionType::parse("blob:123456"),
+            custom(123456, "blob", false)
+        );
+        assert_eq!(
+            ReactionType::parse(":123456"),
+            Some(ReactionType::Custom {
+                id: 123456,
+                name: None,
+                animated: false
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_emojis() {
+        for s in [
+            "",
+            "  ",
+            "<:blob:>",
+            "<:blob:12ab>",
+            "<:blob:-5>",
+            "<:blob:+5>",
+            "<a:blob:99999999999999999999>",
+            "<blob:123456>",
+            "<blob>",
+            "blob:abc",
+        ] {
+            assert_eq!(ReactionType::parse(s), None, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn displays_emojis_as_written_in_content() {
+        let emoji = ReactionType::parse("<a:blob:123456>").unwrap();
+        assert_eq!(emoji.to_string(), "<a:blob:123456>");
+        assert_eq!(ReactionType::from('👍').to_string(), "👍");
+    }
 }
@@ -1,28 +1,318 @@
+// bit flag sets sent by Discord as plain integers
+macro_rules! flags {
+    ($name:ident: $t:ty { $($flag:ident = $bit:expr,)* }) => {
+        #[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+        #[serde(transparent)]
+        pub struct $name(pub $t);
+
+        impl $name {
+            $(pub const $flag: $name = $name(1 << $bit);)*
+
+            pub const fn empty() -> Self {
+                $name(0)
+            }
+
+            pub const fn bits(self) -> $t {
+                self.0
+            }
+
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            pub fn insert(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+
+            pub fn remove(&mut self, other: Self) {
+                self.0 &= !other.0;
+            }
+        }
+
+        impl std::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                $name(self.0 | rhs.0)
+            }
+        }
+
+        impl std::ops::BitAnd for $name {
+            type Output = Self;
+
+            fn bitand(self, rhs: Self) -> Self {
+                $name(self.0 & rhs.0)
+            }
+        }
+    };
+}
+
+// api enums keep values this crate does not know yet in Unknown, so new values
+// discord ships still deserialize, and serialize back unchanged
+macro_rules! int_enum {
+    ($name:ident: $t:ty { $($variant:ident = $value:literal,)* }) => {
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum $name {
+            $($variant,)*
+            Unknown($t),
+        }
+
+        impl $name {
+            pub const fn value(self) -> $t {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl From<$t> for $name {
+            fn from(value: $t) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    value => $name::Unknown(value),
+                }
+            }
+        }
+
+        impl From<$name> for $t {
+            fn from(value: $name) -> Self {
+                value.value()
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                self.value().serialize(s)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                <$t>::deserialize(d).map($name::from)
+            }
+        }
+    };
+}
+
+macro_rules! str_enum {
+    ($name:ident { $($variant:ident = $value:literal,)* }) => {
+        #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+        pub enum $name {
+            $($variant,)*
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    value => $name::Unknown(value.to_string()),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                let value = std::borrow::Cow::<str>::deserialize(d)?;
+                Ok($name::from(&*value))
+            }
+        }
+    };
+}
+
 pub mod audit_logs;
 pub mod channels;
+pub mod content;
 pub mod guilds;
+pub mod markdown;
 pub mod messages;
 pub mod permissions;
 pub mod users;
+pub mod webhooks;
 
 pub mod snowflakes {
     pub type Snowflake = i64;
 
     use chrono::NaiveDateTime;
 
-    const DISCORD_EPOCH: i64 = 1_420_070_400_000; // seconds 
+    const DISCORD_EPOCH: i64 = 1_420_070_400_000; // milliseconds since the unix epoch
 
+    // the time the snowflake was created, to the millisecond
     pub fn snowflake_timestamp(snowflake: Snowflake) -> NaiveDateTime {
-        let s = (snowflake >> 22) + DISCORD_EPOCH;
-        let s = s / 1000;
-        let ms = (s % 1000) as u32;
-        NaiveDateTime::from_timestamp(s, ms)
+        let ms = (snowflake >> 22) + DISCORD_EPOCH;
+        let nanos = ms.rem_euclid(1000) as u32 * 1_000_000;
+        NaiveDateTime::from_timestamp(ms.div_euclid(1000), nanos)
     }
 
     pub fn new(timestamp: NaiveDateTime) -> Snowflake {
         let ms = timestamp.timestamp_millis() - DISCORD_EPOCH;
         ms << 22
     }
+
+    // snowflakes are sent as strings to avoid precision loss in JavaScript;
+    // use with #[serde(with = "snowflakes::string")]
+    pub mod string {
+        use super::Snowflake;
+        use serde::{de, Deserializer, Serializer};
+        use std::fmt;
+
+        struct SnowflakeVisitor;
+
+        impl<'de> de::Visitor<'de> for SnowflakeVisitor {
+            type Value = Snowflake;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a snowflake as a string or integer")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Snowflake, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Snowflake, E> {
+                Ok(v)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Snowflake, E> {
+                Snowflake::try_from(v).map_err(E::custom)
+            }
+        }
+
+        pub fn serialize<S: Serializer>(id: &Snowflake, s: S) -> Result<S::Ok, S::Error> {
+            s.collect_str(id)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Snowflake, D::Error> {
+            d.deserialize_any(SnowflakeVisitor)
+        }
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrapped(#[serde(with = "string")] Snowflake);
+
+    // use with #[serde(default, with = "snowflakes::option")]
+    pub mod option {
+        use super::{Snowflake, Wrapped};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S: Serializer>(id: &Option<Snowflake>, s: S) -> Result<S::Ok, S::Error> {
+            id.map(Wrapped).serialize(s)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Snowflake>, D::Error> {
+            Ok(Option::<Wrapped>::deserialize(d)?.map(|w| w.0))
+        }
+    }
+
+    // use with #[serde(default, with = "snowflakes::vec")]
+    pub mod vec {
+        use super::{Snowflake, Wrapped};
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(ids: &[Snowflake], s: S) -> Result<S::Ok, S::Error> {
+            s.collect_seq(ids.iter().map(|id| Wrapped(*id)))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Snowflake>, D::Error> {
+            Ok(Vec::<Wrapped>::deserialize(d)?.into_iter().map(|w| w.0).collect())
+        }
+    }
 }
 
-pub struct DiscordTimestamp;
\ No newline at end of file
+pub mod cdn {
+    pub const BASE: &str = "https://cdn.discordapp.com";
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum ImageFormat {
+        Png,
+        Jpeg,
+        Webp,
+        Gif,
+    }
+
+    impl ImageFormat {
+        pub fn extension(self) -> &'static str {
+            match self {
+                ImageFormat::Png => "png",
+                ImageFormat::Jpeg => "jpg",
+                ImageFormat::Webp => "webp",
+                ImageFormat::Gif => "gif",
+            }
+        }
+
+        pub fn mime(self) -> &'static str {
+            match self {
+                ImageFormat::Png => "image/png",
+                ImageFormat::Jpeg => "image/jpeg",
+                ImageFormat::Webp => "image/webp",
+                ImageFormat::Gif => "image/gif",
+            }
+        }
+    }
+
+    // image data as the data uri the api takes when uploading icons, banners and avatars
+    pub fn data_uri(bytes: &[u8], format: ImageFormat) -> String {
+        format!("data:{};base64,{}", format.mime(), base64::encode(bytes))
+    }
+
+    // builds "{BASE}/{path}/{hash}.{ext}?size={size}"; animated hashes are prefixed
+    // with "a_" and a gif requested for a static hash falls back to png. sizes are
+    // rounded up to the next power of two between 16 and 4096
+    pub fn url(path: &str, hash: &str, format: ImageFormat, size: u16) -> String {
+        let format = match format {
+            ImageFormat::Gif if !hash.starts_with("a_") => ImageFormat::Png,
+            f => f,
+        };
+        let size = size.clamp(16, 4096).next_power_of_two();
+        format!("{}/{}/{}.{}?size={}", BASE, path, hash, format.extension(), size)
+    }
+}
+
+pub type DiscordTimestamp = chrono::DateTime<chrono::Utc>; // ISO8601 timestamp
+
+#[cfg(test)]
+mod tests {
+    use super::snowflakes::{self, Snowflake};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn snowflake_timestamp_is_exact_to_the_millisecond() {
+        let time = snowflakes::snowflake_timestamp(175928847299117063);
+        let expected = NaiveDate::from_ymd(2016, 4, 30).and_hms_milli(11, 18, 25, 796);
+        assert_eq!(time, expected);
+    }
+
+    #[test]
+    fn new_round_trips_through_snowflake_timestamp() {
+        let time = NaiveDate::from_ymd(2023, 7, 1).and_hms_milli(23, 59, 59, 999);
+        let id: Snowflake = snowflakes::new(time);
+        assert_eq!(snowflakes::snowflake_timestamp(id), time);
+        // the worker, process and increment bits don't change the time
+        assert_eq!(snowflakes::snowflake_timestamp(id | 0x3f_ffff), time);
+        let id: Snowflake = 175928847299117063;
+        let time = snowflakes::snowflake_timestamp(id);
+        assert_eq!(snowflakes::new(time), id >> 22 << 22);
+    }
+}
@@ -1,6 +1,10 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
 use super::snowflakes::Snowflake;
 
-pub enum Permission {
+int_enum!(Permission: u8 {
     CreateInstantInvite = 0,
     KickMembers = 1,
     BanMembers = 2,
@@ -41,41 +45,1088 @@ pub enum Permission {
     UseExternalStickers = 37,
     SendMessagesInThreads = 38,
     StartEmbeddedActivities = 39,
+    ModerateMembers = 40,
+    ViewCreatorMonetizationAnalytics = 41,
+    UseSoundboard = 42,
+    CreateGuildExpressions = 43,
+    CreateEvents = 44,
+    UseExternalSounds = 45,
+    SendVoiceMessages = 46,
+    UseClydeAi = 47,
+    SetVoiceChannelStatus = 48,
+    SendPolls = 49,
+    UseExternalApps = 50,
+});
+
+impl Permission {
+    pub const ALL: [Permission; 51] = [
+        Permission::CreateInstantInvite,
+        Permission::KickMembers,
+        Permission::BanMembers,
+        Permission::Administrator,
+        Permission::ManageChannels,
+        Permission::ManageGuild,
+        Permission::AddReactions,
+        Permission::ViewAuditLog,
+        Permission::PrioritySpeaker,
+        Permission::Stream,
+        Permission::ViewChannel,
+        Permission::SendMessages,
+        Permission::SendTtsMessages,
+        Permission::ManageMessages,
+        Permission::EmbedLinks,
+        Permission::AttachFiles,
+        Permission::ReadMessageHistory,
+        Permission::MentionEveryone,
+        Permission::UseExternalEmojis,
+        Permission::ViewGuildInsights,
+        Permission::Connect,
+        Permission::Speak,
+        Permission::MuteMembers,
+        Permission::DeafenMembers,
+        Permission::MoveMembers,
+        Permission::UseVad,
+        Permission::ChangeNickname,
+        Permission::ManageNicknames,
+        Permission::ManageRoles,
+        Permission::ManageWebhooks,
+        Permission::ManageEmojisAndStickers,
+        Permission::UseApplicationCommands,
+        Permission::RequestToSpeak,
+        Permission::ManageEvents,
+        Permission::ManageThreads,
+        Permission::CreatePublicThreads,
+        Permission::CreatePrivateThreads,
+        Permission::UseExternalStickers,
+        Permission::SendMessagesInThreads,
+        Permission::StartEmbeddedActivities,
+        Permission::ModerateMembers,
+        Permission::ViewCreatorMonetizationAnalytics,
+        Permission::UseSoundboard,
+        Permission::CreateGuildExpressions,
+        Permission::CreateEvents,
+        Permission::UseExternalSounds,
+        Permission::SendVoiceMessages,
+        Permission::UseClydeAi,
+        Permission::SetVoiceChannelStatus,
+        Permission::SendPolls,
+        Permission::UseExternalApps,
+    ];
+
+    pub fn from_bit(bit: u8) -> Permission {
+        Permission::from(bit)
+    }
+
+    pub fn bits(self) -> u64 {
+        1u64.checked_shl(self.value() as u32).unwrap_or(0)
+    }
+
+    // the name shown in the Discord client
+    pub fn name(self) -> &'static str {
+        match self {
+            Permission::CreateInstantInvite => "Create Invite",
+            Permission::KickMembers => "Kick Members",
+            Permission::BanMembers => "Ban Members",
+            Permission::Administrator => "Administrator",
+            Permission::ManageChannels => "Manage Channels",
+            Permission::ManageGuild => "Manage Server",
+            Permission::AddReactions => "Add Reactions",
+            Permission::ViewAuditLog => "View Audit Log",
+            Permission::PrioritySpeaker => "Priority Speaker",
+            Permission::Stream => "Video",
+            Permission::ViewChannel => "View Channel",
+            Permission::SendMessages => "Send Messages",
+            Permission::SendTtsMessages => "Send Text-to-Speech Messages",
+            Permission::ManageMessages => "Manage Messages",
+            Permission::EmbedLinks => "Embed Links",
+            Permission::AttachFiles => "Attach Files",
+            Permission::ReadMessageHistory => "Read Message History",
+            Permission::MentionEveryone => "Mention @everyone, @here, and All Roles",
+            Permission::UseExternalEmojis => "Use External Emojis",
+            Permission::ViewGuildInsights => "View Server Insights",
+            Permission::Connect => "Connect",
+            Permission::Speak => "Speak",
+            Permission::MuteMembers => "Mute Members",
+            Permission::DeafenMembers => "Deafen Members",
+            Permission::MoveMembers => "Move Members",
+            Permission::UseVad => "Use Voice Activity",
+            Permission::ChangeNickname => "Change Nickname",
+            Permission::ManageNicknames => "Manage Nicknames",
+            Permission::ManageRoles => "Manage Roles",
+            Permission::ManageWebhooks => "Manage Webhooks",
+            Permission::ManageEmojisAndStickers => "Manage Emojis and Stickers",
+            Permission::UseApplicationCommands => "Use Application Commands",
+            Permission::RequestToSpeak => "Request to Speak",
+            Permission::ManageEvents => "Manage Events",
+            Permission::ManageThreads => "Manage Threads",
+            Permission::CreatePublicThreads => "Create Public Threads",
+            Permission::CreatePrivateThreads => "Create Private Threads",
+            Permission::UseExternalStickers => "Use External Stickers",
+            Permission::SendMessagesInThreads => "Send Messages in Threads",
+            Permission::StartEmbeddedActivities => "Use Activities",
+            Permission::ModerateMembers => "Timeout Members",
+            Permission::ViewCreatorMonetizationAnalytics => "View Creator Monetization Analytics",
+            Permission::UseSoundboard => "Use Soundboard",
+            Permission::CreateGuildExpressions => "Create Expressions",
+            Permission::CreateEvents => "Create Events",
+            Permission::UseExternalSounds => "Use External Sounds",
+            Permission::SendVoiceMessages => "Send Voice Messages",
+            Permission::UseClydeAi => "Use Clyde AI",
+            Permission::SetVoiceChannelStatus => "Set Voice Channel Status",
+            Permission::SendPolls => "Create Polls",
+            Permission::UseExternalApps => "Use External Apps",
+            Permission::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Permission::Unknown(bit) => write!(f, "Unknown (bit {})", bit),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+// a set of permissions, serialized as Discord's decimal string form; bits not
+// known to Permission are kept so a round trip never drops them
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Permissions(pub u64);
+
+impl Permissions {
+    pub const fn empty() -> Self {
+        Permissions(0)
+    }
+
+    // every permission known to this crate
+    pub fn all() -> Self {
+        Permission::ALL.iter().copied().collect()
+    }
+
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, other: impl Into<Permissions>) -> bool {
+        let other = other.into();
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(self, other: impl Into<Permissions>) -> bool {
+        self.0 & other.into().0 != 0
+    }
+
+    pub fn union(self, other: impl Into<Permissions>) -> Self {
+        Permissions(self.0 | other.into().0)
+    }
+
+    pub fn intersection(self, other: impl Into<Permissions>) -> Self {
+        Permissions(self.0 & other.into().0)
+    }
+
+    pub fn difference(self, other: impl Into<Permissions>) -> Self {
+        Permissions(self.0 & !other.into().0)
+    }
+
+    pub fn insert(&mut self, other: impl Into<Permissions>) {
+        self.0 |= other.into().0;
+    }
+
+    pub fn remove(&mut self, other: impl Into<Permissions>) {
+        self.0 &= !other.into().0;
+    }
+
+    // every set bit in bit order, including ones without a named variant yet
+    pub fn iter(self) -> impl Iterator<Item = Permission> {
+        (0..64)
+            .filter(move |bit| self.0 & (1 << bit) != 0)
+            .map(Permission::from_bit)
+    }
+
+    // bits set in this value that have no Permission variant yet
+    pub fn unknown_bits(self) -> u64 {
+        self.0 & !Permissions::all().0
+    }
+
+    pub fn names(self) -> Vec<&'static str> {
+        self.iter().map(Permission::name).collect()
+    }
+}
+
+impl From<Permission> for Permissions {
+    fn from(permission: Permission) -> Self {
+        Permissions(permission.bits())
+    }
+}
+
+impl FromIterator<Permission> for Permissions {
+    fn from_iter<I: IntoIterator<Item = Permission>>(iter: I) -> Self {
+        Permissions(iter.into_iter().fold(0, |bits, p| bits | p.bits()))
+    }
+}
+
+impl<T: Into<Permissions>> BitOr<T> for Permissions {
+    type Output = Permissions;
+
+    fn bitor(self, rhs: T) -> Permissions {
+        self.union(rhs)
+    }
+}
+
+impl<T: Into<Permissions>> BitAnd<T> for Permissions {
+    type Output = Permissions;
+
+    fn bitand(self, rhs: T) -> Permissions {
+        self.intersection(rhs)
+    }
+}
+
+impl<T: Into<Permissions>> Sub<T> for Permissions {
+    type Output = Permissions;
+
+    fn sub(self, rhs: T) -> Permissions {
+        self.difference(rhs)
+    }
+}
+
+impl<T: Into<Permissions>> BitOrAssign<T> for Permissions {
+    fn bitor_assign(&mut self, rhs: T) {
+        self.insert(rhs);
+    }
+}
+
+impl<T: Into<Permissions>> BitAndAssign<T> for Permissions {
+    fn bitand_assign(&mut self, rhs: T) {
+        *self = self.intersection(rhs);
+    }
+}
+
+impl<T: Into<Permissions>> SubAssign<T> for Permissions {
+    fn sub_assign(&mut self, rhs: T) {
+        self.remove(rhs);
+    }
+}
+
+impl Not for Permissions {
+    type Output = Permissions;
+
+    fn not(self) -> Permissions {
+        Permissions(!self.0)
+    }
+}
+
+impl BitOr for Permission {
+    type Output = Permissions;
+
+    fn bitor(self, rhs: Permission) -> Permissions {
+        Permissions::from(self).union(rhs)
+    }
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<String> = self.iter().map(|p| p.to_string()).collect();
+        f.write_str(&names.join(", "))
+    }
+}
+
+impl Serialize for Permissions {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct PermissionsVisitor;
+
+        impl<'de> de::Visitor<'de> for PermissionsVisitor {
+            type Value = Permissions;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a permission bit set as a decimal string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Permissions, E> {
+                v.parse().map(Permissions).map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Permissions, E> {
+                Ok(Permissions(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Permissions, E> {
+                u64::try_from(v).map(Permissions).map_err(E::custom)
+            }
+        }
+
+        d.deserialize_any(PermissionsVisitor)
+    }
 }
 
 pub mod overwrites {
-    use super::Snowflake;
+    use super::{Permission, Permissions, Snowflake};
+    use crate::types::snowflakes;
+    use serde::{Deserialize, Serialize};
 
+    #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
     pub struct ChannelOverwrite {
-        id: Snowflake,
-        overwrite_type: OverwriteType,
-        allow: u64,
-        deny: u64,
+        #[serde(with = "snowflakes::string")]
+        pub id: Snowflake, // role or user id
+        #[serde(rename = "type")]
+        pub overwrite_type: OverwriteType, // either 0 (role) or 1 (member)
+        pub allow: Permissions, // permission bit set
+        pub deny: Permissions,  // permission bit set
     }
 
-    enum OverwriteType {
-        Role,
-        Member,
+    int_enum!(OverwriteType: u8 {
+        Role = 0,
+        Member = 1,
+    });
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum OverwriteState {
+        Allow,
+        Deny,
+        Inherit,
     }
 
     impl ChannelOverwrite {
-        pub fn allow(&mut self, permission: super::Permission, to: bool) {
-            let bitmask = ((to as u8) << permission as u8) as u64;
-            self.allow = self.allow | bitmask;
-            self.deny = self.deny & !bitmask;
+        pub fn role(id: Snowflake) -> Self {
+            ChannelOverwrite {
+                id,
+                overwrite_type: OverwriteType::Role,
+                allow: Permissions::empty(),
+                deny: Permissions::empty(),
+            }
+        }
+
+        pub fn member(id: Snowflake) -> Self {
+            ChannelOverwrite {
+                id,
+                overwrite_type: OverwriteType::Member,
+                allow: Permissions::empty(),
+                deny: Permissions::empty(),
+            }
+        }
+
+        // each of these accepts a single Permission or a whole Permissions set
+        pub fn allow(&mut self, permissions: impl Into<Permissions>) {
+            let permissions = permissions.into();
+            self.allow.insert(permissions);
+            self.deny.remove(permissions);
+        }
+
+        pub fn deny(&mut self, permissions: impl Into<Permissions>) {
+            let permissions = permissions.into();
+            self.allow.remove(permissions);
+            self.deny.insert(permissions);
+        }
+
+        pub fn inherit(&mut self, permissions: impl Into<Permissions>) {
+            let permissions = permissions.into();
+            self.allow.remove(permissions);
+            self.deny.remove(permissions);
+        }
+
+        pub fn set(&mut self, permissions: impl Into<Permissions>, state: OverwriteState) {
+            match state {
+                OverwriteState::Allow => self.allow(permissions),
+                OverwriteState::Deny => self.deny(permissions),
+                OverwriteState::Inherit => self.inherit(permissions),
+            }
         }
 
-        pub fn deny(&mut self, permission: super::Permission, to: bool) {
-            let bitmask = ((to as u8) << permission as u8) as u64;
-            self.allow = self.allow & !bitmask;
-            self.deny = self.deny | bitmask;
+        pub fn state(&self, permission: Permission) -> OverwriteState {
+            if self.allow.contains(permission) {
+                OverwriteState::Allow
+            } else if self.deny.contains(permission) {
+                OverwriteState::Deny
+            } else {
+                OverwriteState::Inherit
+            }
         }
 
-        pub fn inherit(&mut self, permission: super::Permission, to: bool) {
-            let bitmask = ((to as u8) << permission as u8) as u64;
-            self.allow = self.allow & !bitmask;
-            self.deny = self.deny & !bitmask;
+        // true when the overwrite neither allows nor denies anything
+        pub fn is_empty(&self) -> bool {
+            self.allow.is_empty() && self.deny.is_empty()
         }
     }
 
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub struct PermissionChange {
+        pub permission: Permission,
+        pub before: OverwriteState,
+        pub after: OverwriteState,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub enum OverwriteDiff {
+        Added {
+            id: Snowflake,
+            overwrite_type: OverwriteType,
+            changes: Vec<PermissionChange>,
+        },
+        Removed {
+            id: Snowflake,
+            overwrite_type: OverwriteType,
+            changes: Vec<PermissionChange>,
+        },
+        Changed {
+            id: Snowflake,
+            overwrite_type: OverwriteType,
+            changes: Vec<PermissionChange>,
+        },
+    }
+
+    impl OverwriteDiff {
+        pub fn id(&self) -> Snowflake {
+            match self {
+                OverwriteDiff::Added { id, .. }
+                | OverwriteDiff::Removed { id, .. }
+                | OverwriteDiff::Changed { id, .. } => *id,
+            }
+        }
+
+        pub fn overwrite_type(&self) -> OverwriteType {
+            match self {
+                OverwriteDiff::Added { overwrite_type, .. }
+                | OverwriteDiff::Removed { overwrite_type, .. }
+                | OverwriteDiff::Changed { overwrite_type, .. } => *overwrite_type,
+            }
+        }
+
+        pub fn changes(&self) -> &[PermissionChange] {
+            match self {
+                OverwriteDiff::Added { changes, .. }
+                | OverwriteDiff::Removed { changes, .. }
+                | OverwriteDiff::Changed { changes, .. } => changes,
+            }
+        }
+    }
+
+    fn changes(before: &ChannelOverwrite, after: &ChannelOverwrite) -> Vec<PermissionChange> {
+        (0..64)
+            .map(Permission::from_bit)
+            .map(|p| PermissionChange {
+                permission: p,
+                before: before.state(p),
+                after: after.state(p),
+            })
+            .filter(|c| c.before != c.after)
+            .collect()
+    }
+
+    // compares two overwrite lists, such as the old and new values of a
+    // PermissionOverwrites audit-log change, keyed by role or member id
+    pub fn diff(old: &[ChannelOverwrite], new: &[ChannelOverwrite]) -> Vec<OverwriteDiff> {
+        let find = |list: &[ChannelOverwrite], o: &ChannelOverwrite| {
+            list.iter()
+                .find(|x| x.id == o.id && x.overwrite_type == o.overwrite_type)
+                .cloned()
+        };
+
+        let mut diffs = Vec::new();
+        for before in old {
+            let (id, overwrite_type) = (before.id, before.overwrite_type);
+            match find(new, before) {
+                Some(after) => {
+                    let changes = changes(before, &after);
+                    if !changes.is_empty() {
+                        diffs.push(OverwriteDiff::Changed {
+                            id,
+                            overwrite_type,
+                            changes,
+                        });
+                    }
+                }
+                None => {
+                    let after = ChannelOverwrite {
+                        allow: Permissions::empty(),
+                        deny: Permissions::empty(),
+                        ..before.clone()
+                    };
+                    diffs.push(OverwriteDiff::Removed {
+                        id,
+                        overwrite_type,
+                        changes: changes(before, &after),
+                    });
+                }
+            }
+        }
+        for after in new.iter().filter(|o| find(old, o).is_none()) {
+            let before = ChannelOverwrite {
+                allow: Permissions::empty(),
+                deny: Permissions::empty(),
+                ..after.clone()
+            };
+            diffs.push(OverwriteDiff::Added {
+                id: after.id,
+                overwrite_type: after.overwrite_type,
+                changes: changes(&before, after),
+            });
+        }
+        diffs
+    }
+}
+
+// Discord's permission hierarchy: the @everyone role, then the member's roles,
+// then the channel's @everyone, role and member overwrites, then implicit rules
+pub mod effective {
+    use super::overwrites::{ChannelOverwrite, OverwriteType};
+    use super::{Permission, Permissions, Snowflake};
+    use crate::types::guilds::Guild;
+    use crate::types::users::Member;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum Source {
+        Owner,                    // the member owns the guild
+        Administrator(Snowflake), // a role granting Administrator bypasses everything
+        EveryoneRole,             // the guild's @everyone role
+        Role(Snowflake),          // one of the member's roles
+        EveryoneOverwrite,        // the channel's @everyone overwrite
+        RoleOverwrite(Snowflake), // a channel overwrite for one of the member's roles
+        MemberOverwrite,          // a channel overwrite for the member
+        Implicit(ImplicitRule),   // a rule Discord applies on top of overwrites
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum ImplicitRule {
+        NoViewChannel,  // without View Channel every other channel permission is lost
+        NoSendMessages, // without Send Messages, mentions, tts, files and embeds are lost
+        TimedOut,       // timed out members keep only View Channel and Read Message History
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub struct Reason {
+        pub permission: Permission,
+        pub allowed: bool,
+        pub source: Source, // the last step that decided this permission
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct EffectivePermissions {
+        pub permissions: Permissions,
+        reasons: [Option<(bool, Source)>; 64],
+    }
+
+    impl EffectivePermissions {
+        fn new() -> Self {
+            EffectivePermissions {
+                permissions: Permissions::empty(),
+                reasons: [None; 64],
+            }
+        }
+
+        fn set(&mut self, bits: Permissions, allowed: bool, source: Source) {
+            for bit in 0..64 {
+                if bits.0 & (1 << bit) != 0 {
+                    self.reasons[bit] = Some((allowed, source));
+                }
+            }
+            if allowed {
+                self.permissions.insert(bits);
+            } else {
+                self.permissions.remove(bits);
+            }
+        }
+
+        fn grant_all(&mut self, source: Source) {
+            self.set(Permissions::all(), true, source);
+        }
+
+        pub fn contains(&self, permission: impl Into<Permissions>) -> bool {
+            self.permissions.contains(permission)
+        }
+
+        pub fn reason(&self, permission: Permission) -> Option<Reason> {
+            let (allowed, source) = (*self.reasons.get(permission.value() as usize)?)?;
+            Some(Reason {
+                permission,
+                allowed,
+                source,
+            })
+        }
+
+        // why each permission ended up allowed or denied, in bit order
+        pub fn reasons(&self) -> Vec<Reason> {
+            (0..64)
+                .map(Permission::from_bit)
+                .filter_map(|p| self.reason(p))
+                .collect()
+        }
+    }
+
+    // the guild-level permissions, and whether owner or Administrator bypasses the rest
+    fn base(guild: &Guild, user_id: Snowflake, member: &Member) -> (EffectivePermissions, bool) {
+        let mut effective = EffectivePermissions::new();
+        if user_id == guild.owner_id {
+            effective.grant_all(Source::Owner);
+            return (effective, true);
+        }
+
+        if let Some(everyone) = guild.roles.iter().find(|r| r.id == guild.id) {
+            effective.set(everyone.permissions, true, Source::EveryoneRole);
+        }
+        for role in guild.roles.iter().filter(|r| member.roles.contains(&r.id)) {
+            // only credit bits not already granted by an earlier role
+            let new = role.permissions - effective.permissions;
+            effective.set(new, true, Source::Role(role.id));
+        }
+
+        let admin = guild
+            .roles
+            .iter()
+            .filter(|r| r.id == guild.id || member.roles.contains(&r.id))
+            .find(|r| r.permissions.contains(Permission::Administrator));
+        if let Some(role) = admin {
+            effective.grant_all(Source::Administrator(role.id));
+            return (effective, true);
+        }
+
+        (effective, false)
+    }
+
+    fn timeout(member: &Member, effective: &mut EffectivePermissions) {
+        if member.is_timed_out() {
+            let kept = Permission::ViewChannel | Permission::ReadMessageHistory;
+            let lost = effective.permissions - kept;
+            effective.set(lost, false, Source::Implicit(ImplicitRule::TimedOut));
+        }
+    }
+
+    // guild-wide permissions, ignoring channel overwrites. takes the user id on
+    // its own, as the members in MESSAGE_CREATE and MESSAGE_UPDATE come without
+    // their user
+    pub fn guild_permissions(
+        guild: &Guild,
+        user_id: Snowflake,
+        member: &Member,
+    ) -> EffectivePermissions {
+        let (mut effective, bypass) = base(guild, user_id, member);
+        if !bypass {
+            timeout(member, &mut effective);
+        }
+        effective
+    }
+
+    // takes the user id on its own, as the members in MESSAGE_CREATE and
+    // MESSAGE_UPDATE come without their user
+    pub fn channel_permissions(
+        guild: &Guild,
+        user_id: Snowflake,
+        member: &Member,
+        overwrites: &[ChannelOverwrite],
+    ) -> EffectivePermissions {
+        let (mut effective, bypass) = base(guild, user_id, member);
+        if bypass {
+            return effective;
+        }
+
+        if let Some(everyone) = overwrites.iter().find(|o| o.id == guild.id) {
+            effective.set(everyone.deny, false, Source::EveryoneOverwrite);
+            effective.set(everyone.allow, true, Source::EveryoneOverwrite);
+        }
+
+        // role overwrites act as one combined overwrite, so an allow on any role
+        // wins over a deny on another; each bit is credited to the first role
+        let roles: Vec<&ChannelOverwrite> = overwrites
+            .iter()
+            .filter(|o| o.overwrite_type == OverwriteType::Role)
+            .filter(|o| o.id != guild.id && member.roles.contains(&o.id))
+            .collect();
+        let mut seen = Permissions::empty();
+        for overwrite in &roles {
+            let deny = overwrite.deny - seen;
+            seen.insert(deny);
+            effective.set(deny, false, Source::RoleOverwrite(overwrite.id));
+        }
+        let mut seen = Permissions::empty();
+        for overwrite in &roles {
+            let allow = overwrite.allow - seen;
+            seen.insert(allow);
+            effective.set(allow, true, Source::RoleOverwrite(overwrite.id));
+        }
+
+        let own = overwrites
+            .iter()
+            .filter(|o| o.overwrite_type == OverwriteType::Member)
+            .find(|o| o.id == user_id);
+        if let Some(overwrite) = own {
+            effective.set(overwrite.deny, false, Source::MemberOverwrite);
+            effective.set(overwrite.allow, true, Source::MemberOverwrite);
+        }
+
+        timeout(member, &mut effective);
+
+        if !effective.contains(Permission::ViewChannel) {
+            let lost = effective.permissions;
+            effective.set(lost, false, Source::Implicit(ImplicitRule::NoViewChannel));
+        } else if !effective.contains(Permission::SendMessages) {
+            let dependent = Permission::MentionEveryone
+                | Permission::SendTtsMessages
+                | Permission::AttachFiles
+                | Permission::EmbedLinks;
+            let lost = effective.permissions & dependent;
+            effective.set(lost, false, Source::Implicit(ImplicitRule::NoSendMessages));
+        }
+
+        effective
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::effective::{channel_permissions, guild_permissions, ImplicitRule, Source};
+    use super::overwrites::{
+        self, ChannelOverwrite, OverwriteDiff, OverwriteState, OverwriteType, PermissionChange,
+    };
+    use super::{Permission, Permissions, Snowflake};
+    use crate::types::guilds::Guild;
+    use crate::types::users::Member;
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    fn change(
+        permission: Permission,
+        before: OverwriteState,
+        after: OverwriteState,
+    ) -> PermissionChange {
+        PermissionChange {
+            permission,
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn permissions_round_trip_as_decimal_strings() {
+        let set = Permission::SendMessages | Permission::SetVoiceChannelStatus;
+        let json = serde_json::to_value(set).unwrap();
+        assert_eq!(json, json!(((1u64 << 48) | (1 << 11)).to_string()));
+        assert_eq!(serde_json::from_value::<Permissions>(json).unwrap(), set);
+
+        // older payloads and some gateway events send plain numbers
+        let set: Permissions = serde_json::from_value(json!(1u64 << 47)).unwrap();
+        assert_eq!(set, Permissions::from(Permission::UseClydeAi));
+        assert!(serde_json::from_value::<Permissions>(json!("-1")).is_err());
+    }
+
+    #[test]
+    fn iter_and_names_follow_bit_order() {
+        let set = Permission::SendPolls
+            | Permission::SetVoiceChannelStatus
+            | Permission::UseClydeAi
+            | Permission::SendVoiceMessages
+            | Permission::KickMembers;
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [
+                Permission::KickMembers,
+                Permission::SendVoiceMessages,
+                Permission::UseClydeAi,
+                Permission::SetVoiceChannelStatus,
+                Permission::SendPolls,
+            ]
+        );
+        assert_eq!(
+            set.names(),
+            [
+                "Kick Members",
+                "Send Voice Messages",
+                "Use Clyde AI",
+                "Set Voice Channel Status",
+                "Create Polls",
+            ]
+        );
+        assert_eq!(Permissions::all().iter().count(), Permission::ALL.len());
+        assert_eq!(Permissions::all().unknown_bits(), 0);
+        for (bit, permission) in [
+            (47, Permission::UseClydeAi),
+            (48, Permission::SetVoiceChannelStatus),
+        ] {
+            assert_eq!(Permission::from_bit(bit), permission);
+            assert_eq!(permission.bits(), 1 << bit);
+        }
+    }
+
+    #[test]
+    fn unknown_high_bits_are_kept() {
+        let raw = (1u64 << 62) | (1 << 48) | (1 << 3);
+        let set: Permissions = serde_json::from_value(json!(raw.to_string())).unwrap();
+        assert_eq!(set.bits(), raw);
+        assert_eq!(set.unknown_bits(), 1 << 62);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [
+                Permission::Administrator,
+                Permission::SetVoiceChannelStatus,
+                Permission::Unknown(62),
+            ]
+        );
+        assert_eq!(
+            set.to_string(),
+            "Administrator, Set Voice Channel Status, Unknown (bit 62)"
+        );
+        assert_eq!(serde_json::to_value(set).unwrap(), json!(raw.to_string()));
+    }
+
+    #[test]
+    fn overwrites_handle_bits_above_31() {
+        let mut overwrite = ChannelOverwrite::role(1);
+        overwrite.allow(Permission::ModerateMembers);
+        overwrite.deny(Permission::UseExternalApps);
+        assert_eq!(overwrite.allow.bits(), 1 << 40);
+        assert_eq!(overwrite.deny.bits(), 1 << 50);
+        assert_eq!(
+            overwrite.state(Permission::ModerateMembers),
+            OverwriteState::Allow
+        );
+        assert_eq!(
+            overwrite.state(Permission::UseExternalApps),
+            OverwriteState::Deny
+        );
+        assert_eq!(
+            overwrite.state(Permission::SendMessages),
+            OverwriteState::Inherit
+        );
+
+        overwrite.deny(Permission::ModerateMembers | Permission::SendPolls);
+        assert_eq!(overwrite.allow, Permissions::empty());
+        assert_eq!(overwrite.deny.bits(), 1 << 40 | 1 << 49 | 1 << 50);
+
+        overwrite.set(Permission::SendPolls, OverwriteState::Allow);
+        assert_eq!(overwrite.allow.bits(), 1 << 49);
+        assert_eq!(overwrite.deny.bits(), 1 << 40 | 1 << 50);
+
+        overwrite.inherit(
+            Permission::ModerateMembers | Permission::SendPolls | Permission::UseExternalApps,
+        );
+        assert!(overwrite.is_empty());
+    }
+
+    #[test]
+    fn diff_lists_changed_removed_and_added_overwrites() {
+        let mut changed = ChannelOverwrite::role(1);
+        changed.allow(Permission::ModerateMembers);
+        let mut removed = ChannelOverwrite::member(2);
+        removed.deny(Permission::ViewChannel);
+        let mut unchanged = ChannelOverwrite::role(4);
+        unchanged.allow(Permission::SendPolls);
+        let old = vec![changed.clone(), removed, unchanged.clone()];
+
+        changed.deny(Permission::ModerateMembers);
+        changed.allow(Permission::UseExternalApps);
+        let mut added = ChannelOverwrite::role(3);
+        added.allow(Permission::SendMessages);
+        let new = vec![unchanged, added, changed];
+
+        use OverwriteState::*;
+        assert_eq!(
+            overwrites::diff(&old, &new),
+            vec![
+                OverwriteDiff::Changed {
+                    id: 1,
+                    overwrite_type: OverwriteType::Role,
+                    changes: vec![
+                        change(Permission::ModerateMembers, Allow, Deny),
+                        change(Permission::UseExternalApps, Inherit, Allow),
+                    ],
+                },
+                OverwriteDiff::Removed {
+                    id: 2,
+                    overwrite_type: OverwriteType::Member,
+                    changes: vec![change(Permission::ViewChannel, Deny, Inherit)],
+                },
+                OverwriteDiff::Added {
+                    id: 3,
+                    overwrite_type: OverwriteType::Role,
+                    changes: vec![change(Permission::SendMessages, Inherit, Allow)],
+                },
+            ]
+        );
+    }
+
+    const GUILD: Snowflake = 1;
+    const USER: Snowflake = 5;
+
+    fn guild(everyone: Permissions, roles: &[(Snowflake, Permissions)]) -> Guild {
+        let roles: Vec<_> = [(GUILD, everyone)]
+            .iter()
+            .chain(roles)
+            .map(|(id, permissions)| {
+                json!({ "id": id.to_string(), "name": "role", "permissions": permissions.bits().to_string() })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "id": GUILD.to_string(),
+            "name": "guild",
+            "owner_id": "99",
+            "afk_timeout": 300,
+            "verification_level": 0,
+            "default_message_notifications": 0,
+            "explicit_content_filter": 0,
+            "roles": roles,
+            "emojis": [],
+            "features": [],
+            "mfa_level": 0,
+            "system_channel_flags": 0,
+            "premium_tier": 0,
+            "preferred_locale": "en-US",
+            "nsfw_level": 0,
+            "guild_scheduled_events": [],
+            "premium_progress_bar_enabled": false,
+        }))
+        .unwrap()
+    }
+
+    // as sent in MESSAGE_CREATE, without the user
+    fn member(roles: &[Snowflake]) -> Member {
+        let roles: Vec<_> = roles.iter().map(|id| id.to_string()).collect();
+        serde_json::from_value(json!({ "roles": roles })).unwrap()
+    }
+
+    fn text_permissions() -> Permissions {
+        Permission::ViewChannel
+            | Permission::SendMessages
+            | Permission::ReadMessageHistory
+            | Permission::AttachFiles
+            | Permission::EmbedLinks
+    }
+
+    #[test]
+    fn channel_overwrites_apply_everyone_then_roles_then_member() {
+        let guild = guild(
+            text_permissions(),
+            &[(10, Permissions::empty()), (11, Permissions::empty())],
+        );
+        let mut everyone = ChannelOverwrite::role(GUILD);
+        everyone.deny(Permission::SendMessages | Permission::EmbedLinks);
+        let mut allowed = ChannelOverwrite::role(10);
+        allowed.allow(Permission::SendMessages);
+        let mut denied = ChannelOverwrite::role(11);
+        denied.deny(Permission::SendMessages | Permission::AttachFiles);
+        let mut overwrites = vec![everyone, denied, allowed];
+
+        // an allow on one role wins over a deny on another
+        let effective = channel_permissions(&guild, USER, &member(&[10, 11]), &overwrites);
+        assert!(effective.contains(Permission::SendMessages));
+        assert_eq!(
+            effective.reason(Permission::SendMessages).unwrap().source,
+            Source::RoleOverwrite(10)
+        );
+        assert!(!effective.contains(Permission::AttachFiles));
+        assert_eq!(
+            effective.reason(Permission::AttachFiles).unwrap().source,
+            Source::RoleOverwrite(11)
+        );
+        assert_eq!(
+            effective.reason(Permission::EmbedLinks).unwrap().source,
+            Source::EveryoneOverwrite
+        );
+
+        // the member's own overwrite comes last, found by the id passed in
+        let mut own = ChannelOverwrite::member(USER);
+        own.allow(Permission::AttachFiles);
+        own.deny(Permission::ReadMessageHistory);
+        overwrites.push(own);
+        let effective = channel_permissions(&guild, USER, &member(&[10, 11]), &overwrites);
+        assert!(effective.contains(Permission::AttachFiles));
+        assert_eq!(
+            effective.reason(Permission::AttachFiles).unwrap().source,
+            Source::MemberOverwrite
+        );
+        assert!(!effective.contains(Permission::ReadMessageHistory));
+
+        // overwrites for other users and roles the member lacks don't apply
+        let effective = channel_permissions(&guild, 6, &member(&[11]), &overwrites);
+        assert!(!effective.contains(Permission::SendMessages));
+        assert!(effective.contains(Permission::ReadMessageHistory));
+    }
+
+    #[test]
+    fn the_owner_is_found_by_user_id() {
+        let guild = guild(Permissions::empty(), &[]);
+        let mut everyone = ChannelOverwrite::role(GUILD);
+        everyone.deny(Permission::ViewChannel);
+
+        let effective = channel_permissions(&guild, 99, &member(&[]), &[everyone]);
+        assert_eq!(effective.permissions, Permissions::all());
+        assert_eq!(
+            effective.reason(Permission::ViewChannel).unwrap().source,
+            Source::Owner
+        );
+    }
+
+    #[test]
+    fn the_owner_is_found_by_user_id_without_a_user_on_the_member() {
+        let guild = guild(Permissions::empty(), &[]);
+        let member = member(&[]);
+        assert!(member.user.is_none());
+
+        let effective = guild_permissions(&guild, 99, &member);
+        assert_eq!(effective.permissions, Permissions::all());
+        assert_eq!(
+            effective.reason(Permission::ManageGuild).unwrap().source,
+            Source::Owner
+        );
+
+        let effective = guild_permissions(&guild, USER, &member);
+        assert!(!effective.contains(Permission::ManageGuild));
+    }
+
+    #[test]
+    fn timed_out_members_keep_only_view_channel_and_read_message_history() {
+        let guild = guild(text_permissions(), &[]);
+        let mut member = member(&[]);
+        member.communication_disabled_until = Some(Utc::now() + Duration::hours(1));
+
+        let effective = channel_permissions(&guild, USER, &member, &[]);
+        assert_eq!(
+            effective.permissions,
+            Permission::ViewChannel | Permission::ReadMessageHistory
+        );
+        let reason = effective.reason(Permission::SendMessages).unwrap();
+        assert!(!reason.allowed);
+        assert_eq!(reason.source, Source::Implicit(ImplicitRule::TimedOut));
+
+        member.communication_disabled_until = Some(Utc::now() - Duration::hours(1));
+        let effective = channel_permissions(&guild, USER, &member, &[]);
+        assert_eq!(effective.permissions, text_permissions());
+    }
+
+    #[test]
+    fn implicit_rules_follow_view_channel_and_send_messages() {
+        let guild = guild(text_permissions(), &[]);
+
+        let mut everyone = ChannelOverwrite::role(GUILD);
+        everyone.deny(Permission::ViewChannel);
+        let effective = channel_permissions(&guild, USER, &member(&[]), &[everyone]);
+        assert_eq!(effective.permissions, Permissions::empty());
+        assert_eq!(
+            effective.reason(Permission::SendMessages).unwrap().source,
+            Source::Implicit(ImplicitRule::NoViewChannel)
+        );
+        assert_eq!(
+            effective.reason(Permission::ViewChannel).unwrap().source,
+            Source::EveryoneOverwrite
+        );
+
+        let mut everyone = ChannelOverwrite::role(GUILD);
+        everyone.deny(Permission::SendMessages);
+        let effective = channel_permissions(&guild, USER, &member(&[]), &[everyone]);
+        assert_eq!(
+            effective.permissions,
+            Permission::ViewChannel | Permission::ReadMessageHistory
+        );
+        assert_eq!(
+            effective.reason(Permission::EmbedLinks).unwrap().source,
+            Source::Implicit(ImplicitRule::NoSendMessages)
+        );
+    }
 }
@@ -1,13 +1,253 @@
-use super::snowflakes::Snowflake;
+use serde::{Deserialize, Serialize};
 
+use super::cdn::{self, ImageFormat};
+use super::permissions::Permissions;
+use super::snowflakes::{self, Snowflake};
+use super::DiscordTimestamp;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct User {
-    id: Snowflake,
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // the user's id
+    #[serde(default)]
+    pub username: String, // the user's username, not unique across the platform (missing on partial users such as in presences)
+    pub global_name: Option<String>, // the user's display name, if it is set; for bots, this is the application name
+    #[serde(default)]
+    pub discriminator: String, // the user's Discord-tag, "0" for users on the new username system
+    pub avatar: Option<String>,      // the user's avatar hash
+    #[serde(default)]
+    pub bot: bool, // whether the user belongs to an OAuth2 application
+    #[serde(default)]
+    pub system: bool, // whether the user is an Official Discord System user (part of the urgent message system)
+    pub mfa_enabled: Option<bool>, // whether the user has two factor enabled on their account
+    pub banner: Option<String>,    // the user's banner hash
+    pub accent_color: Option<i32>, // the user's banner color encoded as an integer representation of hexadecimal color code
+    pub locale: Option<String>,    // the user's chosen language option
+    pub verified: Option<bool>,    // whether the email on this account has been verified
+    pub email: Option<String>,     // the user's email
+    pub flags: Option<UserFlags>,  // the flags on a user's account
+    pub premium_type: Option<i32>, // the type of Nitro subscription on a user's account
+    pub public_flags: Option<UserFlags>, // the public flags on a user's account
 }
 
-impl User {}
+impl User {
+    pub fn display_name(&self) -> &str {
+        self.global_name.as_deref().unwrap_or(&self.username)
+    }
+
+    pub fn tag(&self) -> String {
+        match self.discriminator.as_str() {
+            "" | "0" => self.username.clone(),
+            d => format!("{}#{}", self.username, d),
+        }
+    }
+
+    pub fn avatar_url(&self, format: ImageFormat, size: u16) -> Option<String> {
+        let hash = self.avatar.as_deref()?;
+        Some(cdn::url(
+            &format!("avatars/{}", self.id),
+            hash,
+            format,
+            size,
+        ))
+    }
+
+    pub fn default_avatar_url(&self) -> String {
+        // legacy users are indexed by discriminator, migrated users by id
+        let index = match self.discriminator.parse::<i64>() {
+            Ok(d) if d != 0 => d % 5,
+            _ => (self.id >> 22) % 6,
+        };
+        format!("{}/embed/avatars/{}.png", cdn::BASE, index)
+    }
 
-pub struct Member {}
+    // the avatar the client would show: the custom one, or the default otherwise
+    pub fn display_avatar_url(&self, format: ImageFormat, size: u16) -> String {
+        self.avatar_url(format, size)
+            .unwrap_or_else(|| self.default_avatar_url())
+    }
 
-impl Member {}
+    pub fn banner_url(&self, format: ImageFormat, size: u16) -> Option<String> {
+        let hash = self.banner.as_deref()?;
+        Some(cdn::url(
+            &format!("banners/{}", self.id),
+            hash,
+            format,
+            size,
+        ))
+    }
+}
+
+flags!(UserFlags: u64 {
+    STAFF = 0, // Discord Employee
+    PARTNER = 1, // Partnered Server Owner
+    HYPESQUAD = 2, // HypeSquad Events Member
+    BUG_HUNTER_LEVEL_1 = 3, // Bug Hunter Level 1
+    HYPESQUAD_ONLINE_HOUSE_1 = 6, // House Bravery Member
+    HYPESQUAD_ONLINE_HOUSE_2 = 7, // House Brilliance Member
+    HYPESQUAD_ONLINE_HOUSE_3 = 8, // House Balance Member
+    PREMIUM_EARLY_SUPPORTER = 9, // Early Nitro Supporter
+    TEAM_PSEUDO_USER = 10, // User is a team
+    BUG_HUNTER_LEVEL_2 = 14, // Bug Hunter Level 2
+    VERIFIED_BOT = 16, // Verified Bot
+    VERIFIED_DEVELOPER = 17, // Early Verified Bot Developer
+    CERTIFIED_MODERATOR = 18, // Moderator Programs Alumni
+    BOT_HTTP_INTERACTIONS = 19, // Bot uses only HTTP interactions and is shown in the online member list
+    ACTIVE_DEVELOPER = 22, // User is an Active Developer
+});
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Member {
+    pub user: Option<User>, // the user this guild member represents (not included in MESSAGE_CREATE and MESSAGE_UPDATE member objects)
+    pub nick: Option<String>, // this user's guild nickname
+    pub avatar: Option<String>, // the member's guild avatar hash
+    pub banner: Option<String>, // the member's guild banner hash
+    #[serde(default, with = "snowflakes::vec")]
+    pub roles: Vec<Snowflake>, // array of role object ids
+    pub joined_at: Option<DiscordTimestamp>, // when the user joined the guild
+    pub premium_since: Option<DiscordTimestamp>, // when the user started boosting the guild
+    #[serde(default)]
+    pub deaf: bool, // whether the user is deafened in voice channels
+    #[serde(default)]
+    pub mute: bool, // whether the user is muted in voice channels
+    #[serde(default)]
+    pub flags: i32, // guild member flags represented as a bit set, defaults to 0
+    #[serde(default)]
+    pub pending: bool, // whether the user has not yet passed the guild's Membership Screening requirements
+    pub permissions: Option<Permissions>, // total permissions of the member in the channel, including overwrites, returned when in the interaction object
+    pub communication_disabled_until: Option<DiscordTimestamp>, // when the user's timeout will expire and the user will be able to communicate in the guild again, null or a time in the past if the user is not timed out
+}
 
-pub struct PresenceUpdate {}
+impl Member {
+    pub fn display_name(&self) -> Option<&str> {
+        match &self.nick {
+            Some(nick) => Some(nick),
+            None => self.user.as_ref().map(User::display_name),
+        }
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.communication_disabled_until
+            .is_some_and(|until| until > chrono::Utc::now())
+    }
+
+    pub fn avatar_url(
+        &self,
+        guild_id: Snowflake,
+        format: ImageFormat,
+        size: u16,
+    ) -> Option<String> {
+        let hash = self.avatar.as_deref()?;
+        let user_id = self.user.as_ref()?.id;
+        let path = format!("guilds/{}/users/{}/avatars", guild_id, user_id);
+        Some(cdn::url(&path, hash, format, size))
+    }
+
+    // the guild avatar if set, falling back to the user's own avatar
+    pub fn display_avatar_url(
+        &self,
+        guild_id: Snowflake,
+        format: ImageFormat,
+        size: u16,
+    ) -> Option<String> {
+        self.avatar_url(guild_id, format, size)
+            .or_else(|| Some(self.user.as_ref()?.display_avatar_url(format, size)))
+    }
+
+    pub fn banner_url(
+        &self,
+        guild_id: Snowflake,
+        format: ImageFormat,
+        size: u16,
+    ) -> Option<String> {
+        let hash = self.banner.as_deref()?;
+        let user_id = self.user.as_ref()?.id;
+        let path = format!("guilds/{}/users/{}/banners", guild_id, user_id);
+        Some(cdn::url(&path, hash, format, size))
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PresenceUpdate {
+    pub user: User, // the user presence is being updated for
+    #[serde(default, with = "snowflakes::option")]
+    pub guild_id: Option<Snowflake>, // id of the guild
+    pub status: PresenceStatus, // either "idle", "dnd", "online", or "offline"
+    #[serde(default)]
+    pub activities: Vec<Activity>, // user's current activities
+    #[serde(default)]
+    pub client_status: ClientStatus, // user's platform-dependent status
+}
+
+str_enum!(PresenceStatus {
+    Online = "online",
+    Dnd = "dnd",
+    Idle = "idle",
+    Invisible = "invisible",
+    Offline = "offline",
+});
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
+pub struct ClientStatus {
+    pub desktop: Option<PresenceStatus>, // the user's status set for an active desktop (Windows, Linux, Mac) application session
+    pub mobile: Option<PresenceStatus>, // the user's status set for an active mobile (iOS, Android) application session
+    pub web: Option<PresenceStatus>, // the user's status set for an active web (browser, bot user) application session
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Activity {
+    pub name: String, // the activity's name
+    #[serde(rename = "type")]
+    pub activity_type: ActivityType, // activity type
+    pub url: Option<String>, // stream url, is validated when type is 1
+    pub created_at: Option<i64>, // unix timestamp (in milliseconds) of when the activity was added to the user's session
+    pub timestamps: Option<ActivityTimestamps>, // unix timestamps for start and/or end of the game
+    #[serde(default, with = "snowflakes::option")]
+    pub application_id: Option<Snowflake>, // application id for the game
+    pub details: Option<String>, // what the player is currently doing
+    pub state: Option<String>,   // user's current party status, or text used for a custom status
+    pub emoji: Option<ActivityEmoji>, // emoji used for a custom status
+    pub party: Option<ActivityParty>, // information for the current party of the player
+    pub assets: Option<ActivityAssets>, // images for the presence and their hover texts
+    pub instance: Option<bool>,  // whether or not the activity is an instanced game session
+    pub flags: Option<i32>,      // activity flags ORd together, describes what the payload includes
+    #[serde(default)]
+    pub buttons: Vec<String>, // the labels of the custom buttons shown in the Rich Presence (max 2)
+}
+
+int_enum!(ActivityType: u8 {
+    Game = 0,      // Playing {name}
+    Streaming = 1, // Streaming {details}
+    Listening = 2, // Listening to {name}
+    Watching = 3,  // Watching {name}
+    Custom = 4,    // {emoji} {state}
+    Competing = 5, // Competing in {name}
+});
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ActivityTimestamps {
+    pub start: Option<i64>, // unix time (in milliseconds) of when the activity started
+    pub end: Option<i64>,   // unix time (in milliseconds) of when the activity ends
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ActivityEmoji {
+    pub name: String, // the name of the emoji
+    #[serde(default, with = "snowflakes::option")]
+    pub id: Option<Snowflake>, // the id of the emoji
+    pub animated: Option<bool>, // whether this emoji is animated
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ActivityParty {
+    pub id: Option<String>,     // the id of the party
+    pub size: Option<[i32; 2]>, // used to show the party's current and maximum size
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ActivityAssets {
+    pub large_image: Option<String>, // see activity asset image
+    pub large_text: Option<String>, // text displayed when hovering over the large image of the activity
+    pub small_image: Option<String>, // see activity asset image
+    pub small_text: Option<String>, // text displayed when hovering over the small image of the activity
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use super::snowflakes::{self, Snowflake};
+use super::users::User;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Webhook {
+    #[serde(with = "snowflakes::string")]
+    pub id: Snowflake, // the id of the webhook
+    #[serde(rename = "type")]
+    pub webhook_type: WebhookType, // the type of the webhook
+    #[serde(default, with = "snowflakes::option")]
+    pub guild_id: Option<Snowflake>, // the guild id this webhook is for, if any
+    #[serde(default, with = "snowflakes::option")]
+    pub channel_id: Option<Snowflake>, // the channel id this webhook is for, if any
+    pub user: Option<User>, // the user this webhook was created by (not returned when getting a webhook with its token)
+    pub name: Option<String>, // the default name of the webhook
+    pub avatar: Option<String>, // the default user avatar hash of the webhook
+    pub token: Option<String>, // the secure token of the webhook (returned for Incoming Webhooks)
+    #[serde(default, with = "snowflakes::option")]
+    pub application_id: Option<Snowflake>, // the bot/OAuth2 application that created this webhook
+    pub url: Option<String>, // the url used for executing the webhook (returned by the webhooks OAuth2 flow)
+}
+
+int_enum!(WebhookType: u8 {
+    Incoming = 1, // incoming webhooks can post messages to channels with a generated token
+    ChannelFollower = 2, // channel follower webhooks are internal webhooks used with Channel Following to post new messages into channels
+    Application = 3,     // application webhooks are webhooks used with Interactions
+});